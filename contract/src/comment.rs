@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use near_sdk::{serde::{Serialize, Deserialize}, AccountId};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 
@@ -10,16 +12,50 @@ pub struct Comment {
     body: String,
     author: AccountId,
     created_at: u64,
+    updated_at: u64,
+    edited: bool,
+    parent_comment_id: Option<CommentId>,
+    upvotes: HashSet<AccountId>,
 }
 
 impl Comment {
-    pub fn new(comment_id: usize, body: String, author: AccountId, created_at: u64) -> Self {
+    pub fn new(comment_id: usize, body: String, author: AccountId, created_at: u64, parent_comment_id: Option<CommentId>) -> Self {
         Self {
             comment_id,
             body,
             author,
             created_at,
+            updated_at: created_at,
+            edited: false,
+            parent_comment_id,
+            upvotes: HashSet::new(),
+        }
+    }
+
+    pub fn edit(&mut self, body: String, updated_at: u64) {
+        self.body = body;
+        self.updated_at = updated_at;
+        self.edited = true;
+    }
+
+    pub fn is_edited(&self) -> bool {
+        self.edited
+    }
+
+    pub fn get_updated_at(&self) -> u64 {
+        self.updated_at
+    }
+
+    // upvoting twice removes the upvote, returns whether the account is now upvoting
+    pub fn toggle_upvote(&mut self, account_id: AccountId) -> bool {
+        if self.upvotes.remove(&account_id) {
+            return false;
         }
+        self.upvotes.insert(account_id)
+    }
+
+    pub fn get_upvotes(&self) -> HashSet<AccountId> {
+        self.upvotes.clone()
     }
 
     pub fn get_comment_id(&self) -> CommentId {
@@ -29,4 +65,12 @@ impl Comment {
     pub fn get_body(&self) -> String {
         self.body.clone()
     }
+
+    pub fn get_author(&self) -> AccountId {
+        self.author.clone()
+    }
+
+    pub fn get_parent_comment_id(&self) -> Option<CommentId> {
+        self.parent_comment_id
+    }
 }
\ No newline at end of file
@@ -1,31 +1,95 @@
 use near_sdk::AccountId;
+use near_sdk::json_types::U128;
 use near_sdk::serde::{Serialize, Deserialize};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 
-#[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
+#[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct DonationLog {
     donation_id: usize,
-    amount: u128,
+    // gross amount, net_amount is what the author receives after the platform fee
+    // and, for comment tips, the storage of the log
+    amount: U128,
+    net_amount: U128,
     donor: AccountId,
     created_at: u64,
     message: String,
     post_id: usize,
+    // fungible token contract, None for NEAR
+    token: Option<AccountId>,
+    // the NFT contract the donor proved they hold a token of, see `Blog::donate_as_holder`
+    holder_contract: Option<AccountId>,
 }
 
 impl DonationLog {
-    pub fn new(donation_id: usize, amount: u128, donor: AccountId, created_at: u64, message: String, post_id: usize) -> Self {
+    pub fn new(donation_id: usize, amount: u128, donor: AccountId, created_at: u64, message: String, post_id: usize, token: Option<AccountId>) -> Self {
         Self {
             donation_id,
-            amount,
+            amount: U128(amount),
+            net_amount: U128(amount),
             donor,
             created_at,
             message,
             post_id,
+            token,
+            holder_contract: None,
         }
     }
 
+    pub fn deduct_fee(&mut self, fee: u128) {
+        self.net_amount = U128(self.amount.0 - fee);
+    }
+
+    pub fn deduct_storage(&mut self, storage_cost: u128) {
+        self.net_amount = U128(self.net_amount.0 - storage_cost);
+    }
+
+    pub fn get_net_amount(&self) -> u128 {
+        self.net_amount.0
+    }
+
+    // keeps the amount and donor for accounting
+    pub fn redact_message(&mut self) {
+        self.message = String::new();
+    }
+
+    pub fn get_donation_id(&self) -> usize {
+        self.donation_id
+    }
+
+    pub fn get_message(&self) -> String {
+        self.message.clone()
+    }
+
     pub fn get_amount(&self) -> u128 {
-        self.amount
+        self.amount.0
+    }
+
+    pub fn get_donor(&self) -> AccountId {
+        self.donor.clone()
+    }
+
+    pub fn get_post_id(&self) -> usize {
+        self.post_id
+    }
+
+    pub fn get_token(&self) -> Option<AccountId> {
+        self.token.clone()
+    }
+
+    pub fn mark_holder(&mut self, nft_contract: AccountId) {
+        self.holder_contract = Some(nft_contract);
+    }
+
+    pub fn is_holder(&self) -> bool {
+        self.holder_contract.is_some()
+    }
+
+    pub fn get_holder_contract(&self) -> Option<AccountId> {
+        self.holder_contract.clone()
+    }
+
+    pub fn is_near(&self) -> bool {
+        self.token.is_none()
     }
 }
\ No newline at end of file
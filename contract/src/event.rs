@@ -0,0 +1,18 @@
+use near_sdk::env;
+use near_sdk::serde_json::{json, Value};
+
+const EVENT_STANDARD: &str = "decentrablog";
+const EVENT_VERSION: &str = "1.0.0";
+
+/// Logs a NEP-297 event so indexers can parse it, e.g.
+/// `EVENT_JSON:{"standard":"decentrablog","version":"1.0.0","event":"post_created","data":[...]}`
+pub fn log_event(event: &str, data: Value) {
+    let event = json!({
+        "standard": EVENT_STANDARD,
+        "version": EVENT_VERSION,
+        "event": event,
+        "data": [data],
+    });
+
+    env::log(format!("EVENT_JSON:{}", event).as_bytes());
+}
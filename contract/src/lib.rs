@@ -7,35 +7,212 @@
  */
 
 // To conserve gas, efficient serialization is achieved through Borsh (http://borsh.io/)
+use std::collections::HashSet;
 use std::convert::TryInto;
 use comment::Comment;
 use donation::DonationLog;
+use event::log_event;
+use migration::OldBlog;
+use notification::Notification;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::{env, near_bindgen, setup_alloc, AccountId, Promise};
-use near_sdk::collections::{UnorderedMap};
+use near_sdk::{env, ext_contract, near_bindgen, setup_alloc, AccountId, Balance, Gas, Promise, PromiseResult};
+use near_sdk::json_types::U128;
+use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet};
 use near_sdk::serde::{Serialize, Deserialize};
-use post::Post;
+use near_sdk::serde_json::json;
+use post::{Post, PostRevision};
+use profile::Profile;
+use report::Report;
 
 setup_alloc!();
 
 type PostId = usize;
 type CommentId = usize;
 
+// upper bound for a single page, keeps view calls under the gas limit
+const MAX_PAGE_LIMIT: usize = 50;
+
+// length limits are counted in characters, not bytes
+const MAX_TITLE_LENGTH: usize = 200;
+const MAX_BODY_LENGTH: usize = 20_000;
+
+const MAX_TAGS: usize = 5;
+
+const MAX_EXCERPT_LENGTH: usize = 300;
+const MAX_URL_LENGTH: usize = 500;
+
+const MAX_DISPLAY_NAME_LENGTH: usize = 50;
+const MAX_BIO_LENGTH: usize = 500;
+
+const MAX_REPLY_DEPTH: usize = 3;
+
+const REACTIONS: [&str; 4] = ["like", "love", "laugh", "insightful"];
+
+const MAX_FEATURED_POSTS: usize = 10;
+
+const MAX_NONCE_LENGTH: usize = 64;
+
+// keeps create_posts_batch within the gas limit of a single call
+const MAX_BATCH_SIZE: usize = 20;
+
+// views that have to look at every post (sorting, searching) stop after this many,
+// each post read costs storage gas so an uncapped scan fails once the blog grows
+const MAX_SCANNED_POSTS: usize = 500;
+
+// defaults for the owner-configurable comment length, in characters
+const DEFAULT_MIN_COMMENT_LENGTH: usize = 10;
+const DEFAULT_MAX_COMMENT_LENGTH: usize = 1000;
+
+// keeps get_post responses within limits
+const DEFAULT_MAX_COMMENTS_PER_POST: usize = 500;
+
+// 0.01 NEAR, anything smaller doesn't cover the storage of its donation log
+const DEFAULT_MIN_DONATION: Balance = 10_000_000_000_000_000_000_000;
+
+const MAX_DONATION_SPLITS: usize = 10;
+
+// platform fee on NEAR donations, in basis points (1000 = 10%)
+const MAX_DONATION_FEE_BPS: u16 = 1000;
+
+// every post and comment is checked against each banned word
+const MAX_BANNED_WORDS: usize = 100;
+
+// mentions past the first few in a comment are not notified
+const MAX_MENTIONS: usize = 10;
+// older notifications are dropped past this
+const MAX_NOTIFICATIONS: usize = 50;
+
+// minimum time between two posts of the same author, in nanoseconds
+const DEFAULT_POST_COOLDOWN: u64 = 60_000_000_000;
+
+// minimum time between two donations of the same donor to the same post, in nanoseconds
+const DEFAULT_DONATION_COOLDOWN: u64 = 10_000_000_000;
+
+// feeds leave out posts whose net score is at or below this
+const DEFAULT_HIDE_THRESHOLD: i64 = -10;
+
 mod comment;
 mod post;
 mod donation;
+mod event;
+mod migration;
+mod notification;
+mod profile;
+mod report;
+
+// schema version of the stored state, the first deployment was 1
+// bump it together with a new migration
+const STATE_VERSION: u16 = 2;
+
+const GAS_FOR_WITHDRAW_CALLBACK: Gas = 10_000_000_000_000;
+const GAS_FOR_NFT_TOKEN: Gas = 10_000_000_000_000;
+const GAS_FOR_FT_TRANSFER: Gas = 10_000_000_000_000;
+// records the donation, so it needs more than the withdraw callback
+const GAS_FOR_HOLDER_CALLBACK: Gas = 30_000_000_000_000;
+
+#[ext_contract(ext_self)]
+trait BlogCallbacks {
+    fn on_withdraw_complete(&mut self, author: AccountId, amount: U128) -> bool;
+    fn on_holder_verified(&mut self, donation: HolderDonation) -> bool;
+    fn on_ft_withdraw_complete(&mut self, author: AccountId, token: AccountId, amount: U128) -> bool;
+    fn on_fee_withdraw_complete(&mut self, amount: U128) -> bool;
+}
+
+// NEP-141 transfer on the token contract
+#[ext_contract(ext_ft)]
+trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+// NEP-171 view on the NFT contract
+#[ext_contract(ext_nft)]
+trait NonFungibleToken {
+    fn nft_token(&self, token_id: String) -> Option<NftToken>;
+}
+
+// the part of a NEP-171 token the holder check needs
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct NftToken {
+    owner_id: AccountId,
+}
+
+/// A holder donation waiting for the NFT check, `nft_contract` is the contract that was asked.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HolderDonation {
+    pub post_id: PostId,
+    pub donor: AccountId,
+    pub amount: U128,
+    pub message: String,
+    pub nft_contract: AccountId,
+}
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct Blog {
     owner: AccountId,
+    moderators: UnorderedSet<AccountId>,
     user_posts: UnorderedMap<AccountId, Vec<usize>>,
+    tag_posts: UnorderedMap<String, Vec<PostId>>,
     posts: UnorderedMap<PostId, Post>,
     comments: UnorderedMap<CommentId, Comment>,
+    pending_donations: UnorderedMap<AccountId, u128>,
+    storage_paid: UnorderedMap<AccountId, Balance>,
+    following: UnorderedMap<AccountId, Vec<AccountId>>,
+    bookmarks: UnorderedMap<AccountId, Vec<PostId>>,
+    reports: UnorderedMap<PostId, Vec<Report>>,
+    post_viewers: LookupSet<(PostId, AccountId)>,
+    slugs: UnorderedMap<String, PostId>,
+    comment_posts: LookupMap<CommentId, PostId>,
+    last_post_at: UnorderedMap<AccountId, u64>,
+    blocked_commenters: UnorderedMap<AccountId, Vec<AccountId>>,
+    profiles: UnorderedMap<AccountId, Profile>,
+    featured: Vec<PostId>,
+    // lowercase, matched as substrings of posts and comments
+    banned_words: Vec<String>,
+    donor_totals: UnorderedMap<AccountId, Balance>,
+    post_nonces: UnorderedMap<(AccountId, String), PostId>,
+    last_donation_at: LookupMap<(PostId, AccountId), u64>,
+    // (author, token contract) -> token balance waiting for the author
+    pending_ft_donations: UnorderedMap<(AccountId, AccountId), u128>,
+    // token contracts ft_on_transfer accepts, with the minimum donation in that token's units
+    accepted_tokens: UnorderedMap<AccountId, Balance>,
+    // comment ids per commenter, oldest first
+    author_comments: UnorderedMap<AccountId, Vec<CommentId>>,
+    // oldest first
+    notifications: UnorderedMap<AccountId, Vec<Notification>>,
+    // only checked while allowlist_enabled is set
+    allowed_authors: UnorderedSet<AccountId>,
+    // tips to comments, oldest first
+    comment_donations: LookupMap<CommentId, Vec<DonationLog>>,
+    // posts waiting for publish_due to flip them to published
+    scheduled_posts: UnorderedSet<PostId>,
+    // donation id -> the post whose log holds it, comment tips are in donation_comments
+    donation_posts: LookupMap<usize, PostId>,
+    // donation id -> the comment whose tip log holds it
+    donation_comments: LookupMap<usize, CommentId>,
+    // author -> the NFT contract whose holders donate with `donate_as_holder`
+    holder_contracts: LookupMap<AccountId, AccountId>,
+    // post id -> the client nonce it was created with, so removing the post frees the nonce
+    post_client_nonces: LookupMap<PostId, String>,
 
     next_post_id: usize,
     next_comment_id: usize,
     next_donation_id: usize,
+    total_donations: u64,
+    paused: bool,
+    min_donation: Balance,
+    post_cooldown: u64,
+    min_comment_len: usize,
+    max_comment_len: usize,
+    donation_cooldown: u64,
+    max_comments_per_post: usize,
+    version: u16,
+    donation_fee_bps: u16,
+    collected_fees: Balance,
+    allowlist_enabled: bool,
+    hide_threshold: i64,
 }
 
 #[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
@@ -46,546 +223,6512 @@ pub enum VoteStatus {
     None,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BlogStats {
+    total_posts: u64,
+    total_comments: u64,
+    total_donations: u64,
+    next_post_id: usize,
+    total_authors: u64,
+}
+
+// engagement totals over all posts of one author
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuthorSummary {
+    post_count: u64,
+    total_upvotes: u64,
+    total_downvotes: u64,
+    total_comments_received: u64,
+    // NEAR only, what the author was credited after the platform fee
+    total_donated_received: U128,
+}
+
+// a post with its counts precomputed, so the frontend doesn't download the vote sets
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PostView {
+    post: Post,
+    net_score: i64,
+    upvote_count: u64,
+    downvote_count: u64,
+    comment_count: u64,
+    total_donated: U128,
+}
+
+// a post with one page of its comments, `post` has its comment ids left out
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PostPage {
+    post: Post,
+    comments: Vec<Comment>,
+    comment_count: u64,
+}
+
+// msg of an ft_transfer_call donation
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct FtDonationMsg {
+    post_id: PostId,
+    message: Option<String>,
+}
+
+impl PostView {
+    fn new(post: Post) -> Self {
+        Self {
+            net_score: post.get_points(),
+            upvote_count: post.get_upvotes().len() as u64,
+            downvote_count: post.get_downvotes().len() as u64,
+            comment_count: post.get_comments().len() as u64,
+            total_donated: U128(post.get_total_donation()),
+            post,
+        }
+    }
+}
+
 impl Default for Blog {
   fn default() -> Self {
     Self {
       owner: env::signer_account_id(),
+      moderators: UnorderedSet::new(b"moderators".to_vec()),
       user_posts: UnorderedMap::new(b"user_posts".to_vec()),
+      tag_posts: UnorderedMap::new(b"tag_posts".to_vec()),
       posts: UnorderedMap::new(b"posts".to_vec()),
       comments: UnorderedMap::new(b"comments".to_vec()),
+      pending_donations: UnorderedMap::new(b"pending_donations".to_vec()),
+      storage_paid: UnorderedMap::new(b"storage_paid".to_vec()),
+      following: UnorderedMap::new(b"following".to_vec()),
+      bookmarks: UnorderedMap::new(b"bookmarks".to_vec()),
+      reports: UnorderedMap::new(b"reports".to_vec()),
+      post_viewers: LookupSet::new(b"post_viewers".to_vec()),
+      slugs: UnorderedMap::new(b"slugs".to_vec()),
+      comment_posts: LookupMap::new(b"comment_posts".to_vec()),
+      last_post_at: UnorderedMap::new(b"last_post_at".to_vec()),
+      blocked_commenters: UnorderedMap::new(b"blocked_commenters".to_vec()),
+      profiles: UnorderedMap::new(b"profiles".to_vec()),
+      featured: Vec::new(),
+      banned_words: Vec::new(),
+      donor_totals: UnorderedMap::new(b"donor_totals".to_vec()),
+      post_nonces: UnorderedMap::new(b"post_nonces".to_vec()),
+      last_donation_at: LookupMap::new(b"last_donation_at".to_vec()),
+      pending_ft_donations: UnorderedMap::new(b"pending_ft_donations".to_vec()),
+      accepted_tokens: UnorderedMap::new(b"accepted_tokens".to_vec()),
+      author_comments: UnorderedMap::new(b"author_comments".to_vec()),
+      notifications: UnorderedMap::new(b"notifications".to_vec()),
+      allowed_authors: UnorderedSet::new(b"allowed_authors".to_vec()),
+      comment_donations: LookupMap::new(b"comment_donations".to_vec()),
+      scheduled_posts: UnorderedSet::new(b"scheduled_posts".to_vec()),
+      donation_posts: LookupMap::new(b"donation_posts".to_vec()),
+      donation_comments: LookupMap::new(b"donation_comments".to_vec()),
+      post_client_nonces: LookupMap::new(b"post_client_nonces".to_vec()),
+      holder_contracts: LookupMap::new(b"holder_contracts".to_vec()),
 
       next_post_id: 0,
       next_comment_id: 0,
       next_donation_id: 0,
+      total_donations: 0,
+      paused: false,
+      min_donation: DEFAULT_MIN_DONATION,
+      post_cooldown: DEFAULT_POST_COOLDOWN,
+      min_comment_len: DEFAULT_MIN_COMMENT_LENGTH,
+      max_comment_len: DEFAULT_MAX_COMMENT_LENGTH,
+      donation_cooldown: DEFAULT_DONATION_COOLDOWN,
+      max_comments_per_post: DEFAULT_MAX_COMMENTS_PER_POST,
+      version: STATE_VERSION,
+      donation_fee_bps: 0,
+      collected_fees: 0,
+      allowlist_enabled: false,
+      hide_threshold: DEFAULT_HIDE_THRESHOLD,
     }
   }
 }
 
 #[near_bindgen]
 impl Blog {
-    pub fn create_post(&mut self, title: String, body: String) -> usize {
-        let post_id = self.next_post_id;
+    /// Initializes a fresh deployment with an explicit owner.
+    /// Prefer this over relying on `Default`, which makes the first signer the owner.
+    #[init]
+    pub fn new(owner: AccountId) -> Self {
+        assert!(!env::state_exists(), "The contract has already been initialized");
+        assert!(env::is_valid_account_id(owner.as_bytes()), "Invalid account id");
 
-        let post =  Post::new(post_id, title, body, env::predecessor_account_id(), env::block_timestamp());
-        
-        self.posts.insert(&post_id, &post);
-        self.next_post_id = self.next_post_id + 1;
+        Self {
+            owner,
+            ..Default::default()
+        }
+    }
 
-        //push to user's post list
-        let mut user_posts = self.user_posts.get(&env::predecessor_account_id()).unwrap_or(vec![]);
-        
-        user_posts.push(post_id);
-        self.user_posts.insert(&env::predecessor_account_id(), &user_posts); 
+    /// Upgrades state written by the first deployment (see `migration::OldBlog`).
+    /// Every stored post and comment is rewritten, so this costs gas per record.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let mut old_state: OldBlog = env::state_read().expect("Old state does not exist");
 
-        let title = post.get_title();
+        let mut old_posts = old_state.posts.to_vec();
+        // donation ids are handed out in post order
+        old_posts.sort_by_key(|(post_id, _)| *post_id);
+        let old_comments = old_state.comments.to_vec();
+        old_state.posts.clear();
+        old_state.comments.clear();
 
-        // Use env::log to record logs permanently to the blockchain!
-        env::log(format!("Post '{}' was created", title).as_bytes());
+        // same prefixes, so the new maps take over the old storage
+        let mut blog = Self {
+            owner: old_state.owner,
+            user_posts: old_state.user_posts,
+            next_post_id: old_state.next_post_id,
+            next_comment_id: old_state.next_comment_id,
+            // the old code never advanced the donation id, every old log has id 0
+            next_donation_id: 0,
+            ..Default::default()
+        };
+
+        for (post_id, old_post) in old_posts {
+            let mut post = Post::new(post_id, old_post.title, old_post.body, old_post.author, old_post.created_at, vec![], true);
+            blog.assign_slug(&mut post);
+
+            for comment_id in old_post.comments {
+                post.add_comment(comment_id);
+                blog.comment_posts.insert(&comment_id, &post_id);
+            }
+            for account_id in old_post.upvotes {
+                post.add_upvote(account_id);
+            }
+            for account_id in old_post.downvotes {
+                post.add_downvote(account_id);
+            }
+            for old_donation_log in old_post.donation_logs {
+                let donated = blog.donor_totals.get(&old_donation_log.donor).unwrap_or(0);
+                blog.donor_totals.insert(&old_donation_log.donor, &(donated + old_donation_log.amount));
+
+                let donation_log = DonationLog::new(
+                    blog.next_donation_id,
+                    old_donation_log.amount,
+                    old_donation_log.donor,
+                    old_donation_log.created_at,
+                    old_donation_log.message,
+                    old_donation_log.post_id,
+                    None,
+                );
+                blog.donation_posts.insert(&donation_log.get_donation_id(), &post_id);
+                post.add_donation_logs(donation_log);
+                blog.next_donation_id += 1;
+                blog.total_donations += 1;
+            }
+
+            blog.posts.insert(&post_id, &post);
+        }
+
+        for (comment_id, old_comment) in old_comments {
+            let comment = Comment::new(comment_id, old_comment.body, old_comment.author, old_comment.created_at, None);
+            blog.comments.insert(&comment_id, &comment);
+
+            let mut author_comments = blog.author_comments.get(&comment.get_author()).unwrap_or_default();
+            author_comments.push(comment_id);
+            blog.author_comments.insert(&comment.get_author(), &author_comments);
+        }
+
+        // the old delete_post left the ids of deleted posts in user_posts
+        for (author, mut user_posts) in blog.user_posts.to_vec() {
+            user_posts.retain(|post_id| blog.posts.get(post_id).is_some());
+            if user_posts.is_empty() {
+                blog.user_posts.remove(&author);
+            } else {
+                blog.user_posts.insert(&author, &user_posts);
+            }
+        }
+
+        blog.version = STATE_VERSION;
+
+        blog
+    }
+
+    #[payable]
+    /// Without an excerpt the first 160 characters of the body are used.
+    pub fn create_post(&mut self, title: String, body: String, tags: Option<Vec<String>>, nsfw: Option<bool>, cover_image_url: Option<String>, excerpt: Option<String>) -> usize {
+        self.add_post(title, body, tags, nsfw.unwrap_or(false), cover_image_url.unwrap_or_default(), excerpt.unwrap_or_default(), true, None)
+    }
+
+    /// Same as `create_post`, but safe to retry: a nonce the caller already used
+    /// returns the post created with it and refunds the deposit.
+    #[payable]
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_post_with_nonce(&mut self, title: String, body: String, tags: Option<Vec<String>>, nsfw: Option<bool>, cover_image_url: Option<String>, excerpt: Option<String>, client_nonce: String) -> usize {
+        self.assert_not_paused();
+        assert!(!client_nonce.is_empty(), "Nonce must not be empty");
+        assert!(client_nonce.len() <= MAX_NONCE_LENGTH, "Nonce must be at most {} bytes long", MAX_NONCE_LENGTH);
+        let initial_storage_usage = env::storage_usage();
+
+        let key = (env::predecessor_account_id(), client_nonce);
+        if let Some(post_id) = self.post_nonces.get(&key) {
+            self.charge_storage(initial_storage_usage);
+            return post_id;
+        }
+
+        // a replayed nonce skips these, the post already passed them
+        let cover_image_url = cover_image_url.unwrap_or_default();
+        let excerpt = excerpt.unwrap_or_default();
+        self.assert_can_post();
+        self.assert_valid_new_post(&title, &body);
+        assert_valid_post_extras(&cover_image_url, &excerpt);
+
+        let post_id = self.insert_post(title, body, tags, nsfw.unwrap_or(false), cover_image_url, excerpt, true, None);
+        self.post_nonces.insert(&key, &post_id);
+        self.post_client_nonces.insert(&post_id, &key.1);
+
+        self.charge_storage(initial_storage_usage);
 
         post_id
     }
 
-    pub fn get_owner(&self) -> AccountId {
-        self.owner.clone()
+    #[payable]
+    pub fn create_draft(&mut self, title: String, body: String, tags: Option<Vec<String>>, nsfw: Option<bool>, cover_image_url: Option<String>, excerpt: Option<String>) -> usize {
+        self.add_post(title, body, tags, nsfw.unwrap_or(false), cover_image_url.unwrap_or_default(), excerpt.unwrap_or_default(), false, None)
     }
 
-    pub fn get_post(&self, post_id: usize) -> Option<Post> {
-        self.posts.get(&post_id)
+    /// Stores the post as a draft that is listed from `publish_at` (nanoseconds) on.
+    /// A `publish_at` in the past lists it right away.
+    #[payable]
+    pub fn schedule_post(&mut self, title: String, body: String, publish_at: u64) -> usize {
+        self.add_post(title, body, None, false, String::new(), String::new(), false, Some(publish_at))
     }
 
-    pub fn get_posts(&self) -> Vec<Post> {
-        let mut posts = Vec::new();
+    /// Marks scheduled posts whose time has come as published, returns how many were flipped.
+    /// Feeds already list due posts, this only cleans up the stored flag.
+    pub fn publish_due(&mut self) -> u64 {
+        let due: Vec<PostId> = self.scheduled_posts
+            .iter()
+            .take(MAX_SCANNED_POSTS)
+            .filter(|post_id| self.posts.get(post_id).is_some_and(|post| post.is_due()))
+            .collect();
 
-        for post_id in self.posts.keys() {
-            posts.push(self.posts.get(&post_id).unwrap());
+        let mut published = 0;
+        for post_id in due {
+            self.scheduled_posts.remove(&post_id);
+            if let Some(mut post) = self.posts.get(&post_id) {
+                post.publish();
+                self.posts.insert(&post_id, &post);
+                published += 1;
+            }
         }
 
-        posts
+        published
     }
 
-    pub fn get_user_posts(&self, user_id: AccountId) -> Vec<Post> {
-        //if user_id has no post by checking length
-        if self.user_posts.get(&user_id).unwrap_or(vec![]).len() == 0 {
-            return vec![];
+    /// Imports (title, body) pairs in one call. The batch counts as a single post
+    /// for the cooldown and the deposit has to cover the storage of all of them.
+    #[payable]
+    pub fn create_posts_batch(&mut self, posts: Vec<(String, String)>) -> Vec<usize> {
+        self.assert_can_post();
+        assert!(!posts.is_empty(), "Batch must contain at least one post");
+        assert!(posts.len() <= MAX_BATCH_SIZE, "Batch can contain at most {} posts", MAX_BATCH_SIZE);
+        for (title, body) in posts.iter() {
+            self.assert_valid_new_post(title, body);
         }
+        let initial_storage_usage = env::storage_usage();
 
-        let mut posts = Vec::new();
+        let post_ids = posts
+            .into_iter()
+            .map(|(title, body)| self.insert_post(title, body, None, false, String::new(), String::new(), true, None))
+            .collect();
 
-        for post_id in self.user_posts.get(&user_id).unwrap() {
-            posts.push(self.posts.get(&post_id).unwrap());
-        }
+        self.charge_storage(initial_storage_usage);
 
-        posts
+        post_ids
     }
 
-    pub fn get_paging_posts(&self, page: usize, page_size: usize) -> Vec<Post> {
-        assert!(page_size > 0, "Page size must be greater than 0");
-        assert!(page > 0, "Page must be greater than 0");
+    pub fn publish_post(&mut self, post_id: usize) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+        assert_eq!(post.get_author(), env::predecessor_account_id(), "Only author can publish the post");
+        assert!(!post.is_published(), "Post is already published");
 
-        let mut posts = Vec::new();
+        post.publish();
+        self.posts.insert(&post_id, &post);
+        self.scheduled_posts.remove(&post_id);
+    }
 
+    // takes the post offline, comments and donations are kept for when it is published again
+    pub fn unpublish_post(&mut self, post_id: usize) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+        assert_eq!(post.get_author(), env::predecessor_account_id(), "Only author can unpublish the post");
+        assert!(post.is_published(), "Post is not published");
 
-        //notice: page start from 1
-        let start = (page - 1) * page_size;
-        let mut end = start + page_size;
-    
-        let total_posts = self.posts.len().try_into().unwrap_or(0);
+        post.unpublish();
+        self.posts.insert(&post_id, &post);
+        self.scheduled_posts.remove(&post_id);
+    }
 
-        if end > total_posts {
-            end = total_posts;
-        }
+    pub fn set_comments_enabled(&mut self, post_id: usize, enabled: bool) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+        assert_eq!(post.get_author(), env::predecessor_account_id(), "Only author can enable or disable comments");
 
-        let values = self.posts.values();
+        post.set_comments_enabled(enabled);
+        self.posts.insert(&post_id, &post);
+    }
 
-        for value in values.skip(start).take(end - start) {
-            posts.push(value);
-        }
+    pub fn pin_comment(&mut self, post_id: usize, comment_id: usize) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+        assert_eq!(post.get_author(), env::predecessor_account_id(), "Only author can pin comments");
+        assert!(post.get_comments().contains(&comment_id), "Comment does not exist");
 
-        posts
+        post.pin_comment(comment_id);
+        self.posts.insert(&post_id, &post);
     }
 
-    pub fn get_total_posts(&self) -> u64 {
-        self.posts.len()
+    pub fn unpin_comment(&mut self, post_id: usize) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+        assert_eq!(post.get_author(), env::predecessor_account_id(), "Only author can unpin comments");
+
+        post.unpin_comment();
+        self.posts.insert(&post_id, &post);
     }
 
-    pub fn delete_post(&mut self, post_id: usize) {
-        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can delete posts");
-        self.posts.remove(&post_id);
+    pub fn get_version(&self) -> u16 {
+        self.version
     }
 
-    pub fn create_comment(&mut self, post_id: usize, body: String) {
-        // Check if the post exists
-        let post = self.posts.get(&post_id);
-        assert!(post.is_some(), "Post does not exist");
-        assert!(body.len() >= 10, "Comment must be at least 10 characters long");
+    pub fn get_owner(&self) -> AccountId {
+        self.owner.clone()
+    }
 
-        let author = env::predecessor_account_id();
-        let created_at = env::block_timestamp();
+    pub fn transfer_ownership(&mut self, new_owner: AccountId) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can transfer ownership");
+        assert!(env::is_valid_account_id(new_owner.as_bytes()), "Invalid account id");
 
-        let comment = Comment::new(self.next_comment_id, body, author, created_at);
+        env::log(format!("Ownership transferred from {} to {}", self.owner, new_owner).as_bytes());
+        self.owner = new_owner;
+    }
 
-        match self.posts.get(&post_id).as_mut() {
-            Some(post) => {
-                post.add_comment(comment.get_comment_id());
-                self.posts.insert(&post_id, post);
-            },
-            None => panic!("Post does not exist"),
-        }
+    // owner and moderator actions keep working while paused
+    pub fn set_paused(&mut self, paused: bool) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can pause the contract");
 
-        self.comments.insert(&comment.get_comment_id(), &comment);
-        self.next_comment_id = self.next_comment_id + 1;
+        self.paused = paused;
+        env::log(format!("Contract paused: {}", paused).as_bytes());
     }
 
-    pub fn delete_comment(&mut self, post_id: usize, comment_id: usize) {
-        // only owner can delete comments
-        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can delete comments");
-
-        // Check if the post exists
-        let mut post = match self.posts.get(&post_id) {
-            Some(post) => post,
-            None => panic!("Post does not exist"),
-        };
-        
-        let comment = self.comments.get(&comment_id).unwrap();
-        assert!(comment.get_comment_id() == comment_id, "Comment does not exist");
-        
-        post.remove_comment(comment_id);
+    pub fn is_paused(&self) -> bool {
+        self.paused
     }
 
-    #[payable]
-    pub fn donate(&mut self, post_id: usize, amount: u128, message: String) {
-        // Check if the post exists
-        let post = self.posts.get(&post_id).unwrap();
-        assert!(post.get_post_id() == post_id, "Post does not exist");
+    pub fn set_min_donation(&mut self, amount: U128) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can set the minimum donation");
 
-        // check if the amount is valid
-        assert!(amount >= 1, "Amount must be greater than 0");
-        // enough balance
-        assert!(env::account_balance() >= amount, "Not enough balance");
+        self.min_donation = amount.0;
+    }
 
+    pub fn get_min_donation(&self) -> U128 {
+        U128(self.min_donation)
+    }
 
-        // transfer NEAR to the post author
-        let author = post.get_author();
-        let amount = amount;
-        
-        Promise::new(author).transfer(amount).then(self.save_to_donation_log(post_id, amount, message));
+    pub fn set_post_cooldown(&mut self, seconds: u64) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can set the post cooldown");
+
+        self.post_cooldown = seconds * 1_000_000_000;
     }
 
-    #[private]
-    fn save_to_donation_log(&mut self, post_id: usize, amount: u128, message: String) -> Promise {
-        let created_at = env::block_timestamp();
+    // in seconds
+    pub fn get_post_cooldown(&self) -> u64 {
+        self.post_cooldown / 1_000_000_000
+    }
 
-        let donation_log = DonationLog::new(self.next_donation_id, amount, env::predecessor_account_id(), created_at, message, post_id);
+    pub fn set_hide_threshold(&mut self, threshold: i64) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can set the hide threshold");
 
-        self.next_comment_id = self.next_comment_id + 1;
+        self.hide_threshold = threshold;
+    }
 
-        // save to donation log
-        let mut post = self.posts.get(&post_id).unwrap();
-        post.add_donation_logs(donation_log);
-        self.posts.insert(&post_id, &post);
+    pub fn get_hide_threshold(&self) -> i64 {
+        self.hide_threshold
+    }
 
-        let donor = env::predecessor_account_id();
+    pub fn set_donation_fee(&mut self, fee_bps: u16) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can set the donation fee");
+        assert!(fee_bps <= MAX_DONATION_FEE_BPS, "Donation fee must be at most {} basis points", MAX_DONATION_FEE_BPS);
 
-        //Mark the promise as fulfilled by doing nothing
-        Promise::new(donor)
+        self.donation_fee_bps = fee_bps;
     }
 
-    pub fn get_next_post_id(&self) -> usize {
-        self.next_post_id
+    pub fn get_donation_fee(&self) -> u16 {
+        self.donation_fee_bps
     }
 
-    pub fn get_comments(&self, post_id: usize) -> Vec<Comment> {
-        let post = self.posts.get(&post_id).unwrap();
-        
-        let mut comments = Vec::new();
-        for comment_id in post.get_comments() {
-            comments.push(self.comments.get(&comment_id).unwrap());
-        }
-        comments
+    pub fn get_collected_fees(&self) -> U128 {
+        U128(self.collected_fees)
     }
 
-    pub fn get_paging_comments(self, post_id: usize, page: usize, page_size: usize) -> Vec<Comment> {
-        assert!(page_size > 0, "Page size must be greater than 0");
-        assert!(page > 0, "Page must be greater than 0");
+    pub fn withdraw_fees(&mut self, amount: U128) -> Promise {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can withdraw fees");
+        assert!(amount.0 > 0, "Amount must be greater than 0");
+        assert!(amount.0 <= self.collected_fees, "Amount exceeds the collected fees");
 
-        let post = self.posts.get(&post_id).unwrap();
-        let mut comments = Vec::new();
+        self.collected_fees -= amount.0;
+        Promise::new(self.owner.clone()).transfer(amount.0).then(ext_self::on_fee_withdraw_complete(
+            amount,
+            &env::current_account_id(),
+            0,
+            GAS_FOR_WITHDRAW_CALLBACK,
+        ))
+    }
 
-        let mut start = (page - 1) * page_size;
-        let mut end = start + page_size;
-         
-        if end > post.get_comments().len() {
-            end = post.get_comments().len();
-        }
+    #[private]
+    pub fn on_fee_withdraw_complete(&mut self, amount: U128) -> bool {
+        assert_eq!(env::promise_results_count(), 1, "Expected one promise result");
 
-        for comment_id in post.get_comments() {
-            if start < end {
-                start = start + 1;
-                comments.push(self.comments.get(&comment_id).unwrap());
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => true,
+            _ => {
+                // the transfer failed, put the fees back
+                self.collected_fees += amount.0;
+                false
             }
         }
+    }
 
-        comments
+    pub fn set_donation_cooldown(&mut self, seconds: u64) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can set the donation cooldown");
+
+        self.donation_cooldown = seconds * 1_000_000_000;
     }
 
-    pub fn get_total_comments(&self) -> u64 {
-        self.comments.len()
+    // in seconds
+    pub fn get_donation_cooldown(&self) -> u64 {
+        self.donation_cooldown / 1_000_000_000
     }
 
-    pub fn get_comment(&self, comment_id: usize) -> Comment {
-        self.comments.get(&comment_id).unwrap()
+    pub fn set_comment_length_limits(&mut self, min: usize, max: usize) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can set the comment length limits");
+        assert!(min <= max, "Minimum comment length must not exceed the maximum");
+
+        self.min_comment_len = min;
+        self.max_comment_len = max;
     }
 
-    pub fn get_post_total_comments(&self, post_id: usize) -> u64 {
-        let post = match self.posts.get(&post_id) {
-            Some(post) => post,
-            None => panic!("Post does not exist"),
-        };
-        
-        post.get_comments().len().try_into().unwrap()
+    pub fn get_comment_length_limits(&self) -> (usize, usize) {
+        (self.min_comment_len, self.max_comment_len)
     }
 
-    pub fn upvote(&mut self, post_id: usize) {
-        let voter = env::predecessor_account_id(); 
-        
-        
-        match self.posts.get(&post_id).as_mut() {
-            Some(post) => {
-                post.add_upvote(voter);
-                self.posts.insert(&post_id, post);
-            },
-            None => panic!("Post does not exist"),
-        }
+    pub fn set_max_comments_per_post(&mut self, max_comments: usize) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can set the comment limit");
+
+        self.max_comments_per_post = max_comments;
     }
 
-    pub fn remove_upvote(&mut self, post_id: usize) {
-        let mut post = match self.posts.get(&post_id) {
-            Some(post) => post,
-            None => panic!("Post does not exist"),
-        };
+    pub fn get_max_comments_per_post(&self) -> usize {
+        self.max_comments_per_post
+    }
 
-        let voter = env::predecessor_account_id(); 
-        post.remove_upvote(voter);
-        
-        self.posts.insert(&post_id, &post);
+    pub fn add_moderator(&mut self, account_id: AccountId) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can add moderators");
+        assert!(env::is_valid_account_id(account_id.as_bytes()), "Invalid account id");
+
+        self.moderators.insert(&account_id);
     }
 
-    pub fn downvote(&mut self, post_id: usize) {
-        let voter = env::predecessor_account_id(); 
+    pub fn remove_moderator(&mut self, account_id: AccountId) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can remove moderators");
 
-        match self.posts.get(&post_id).as_mut() {
-            Some(post) => {
-                post.add_downvote(voter);
-                self.posts.insert(&post_id, post);
-            },
-            None => panic!("Post does not exist"),
-        }
+        self.moderators.remove(&account_id);
     }
 
-    pub fn remove_downvote(&mut self, post_id: usize) {
-        let mut post =  match self.posts.get(&post_id) {
-            Some(post) => post,
-            None => panic!("Post does not exist"),
-        };        
-        let voter = env::predecessor_account_id(); 
-        post.remove_downvote(voter);
-        
-        self.posts.insert(&post_id, &post);
+    pub fn is_moderator(&self, account_id: AccountId) -> bool {
+        self.moderators.contains(&account_id)
     }
 
-    pub fn get_votes_statistics(&self, post_id: usize) -> (usize, usize) {
-        let post =  match self.posts.get(&post_id) {
-            Some(post) => post,
-            None => panic!("Post does not exist"),
-        };         
-        let upvotes = post.get_upvotes().len();
-        let downvotes = post.get_downvotes().len();
+    pub fn get_moderators(&self) -> Vec<AccountId> {
+        self.moderators.to_vec()
+    }
 
-        (upvotes, downvotes)
+    pub fn set_allowlist_enabled(&mut self, enabled: bool) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can enable or disable the allowlist");
+
+        self.allowlist_enabled = enabled;
     }
 
-    pub fn get_user_vote_status(&self, post_id: usize, user_id: AccountId) -> VoteStatus {
-        let post =  match self.posts.get(&post_id) {
-            Some(post) => post,
-            None => panic!("Post does not exist"),
-        };        
-        let voter = user_id;
-        let upvotes = post.get_upvotes().contains(&voter);
-        let downvotes = post.get_downvotes().contains(&voter);
+    pub fn is_allowlist_enabled(&self) -> bool {
+        self.allowlist_enabled
+    }
 
-        if upvotes && !downvotes {
-            VoteStatus::Upvoted
-        } else if !upvotes && downvotes {
-            VoteStatus::Downvoted
-        } else {
-            VoteStatus::None
-        }
+    pub fn add_author(&mut self, account_id: AccountId) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can add authors");
+        assert!(env::is_valid_account_id(account_id.as_bytes()), "Invalid account id");
+
+        self.allowed_authors.insert(&account_id);
     }
 
-}
+    pub fn remove_author(&mut self, account_id: AccountId) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can remove authors");
 
-/*
- * The rest of this file holds the inline tests for the code above
- * Learn more about Rust tests: https://doc.rust-lang.org/book/ch11-01-writing-tests.html
- *
- * To run from contract directory:
- * cargo test -- --nocapture
- *
- * From project root, to run in combination with frontend tests:
- * yarn test
+        self.allowed_authors.remove(&account_id);
+    }
+
+    pub fn is_allowed_author(&self, account_id: AccountId) -> bool {
+        self.allowed_authors.contains(&account_id)
+    }
+
+    pub fn add_banned_word(&mut self, word: String) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can ban words");
+        let word = word.trim().to_lowercase();
+        assert!(!word.is_empty(), "Word must not be empty");
+        assert!(!self.banned_words.contains(&word), "Word is already banned");
+        assert!(self.banned_words.len() < MAX_BANNED_WORDS, "At most {} words can be banned", MAX_BANNED_WORDS);
+
+        self.banned_words.push(word);
+    }
+
+    pub fn remove_banned_word(&mut self, word: String) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can unban words");
+        let word = word.trim().to_lowercase();
+
+        self.banned_words.retain(|x| *x != word);
+    }
+
+    pub fn get_banned_words(&self) -> Vec<String> {
+        self.banned_words.clone()
+    }
+
+    pub fn feature_post(&mut self, post_id: usize) {
+        assert!(self.can_moderate(&env::predecessor_account_id()), "Only owner or moderators can feature posts");
+        let post = self.require_post(post_id);
+        assert!(post.is_published(), "Drafts cannot be featured");
+        assert!(!self.featured.contains(&post_id), "Post is already featured");
+        assert!(self.featured.len() < MAX_FEATURED_POSTS, "At most {} posts can be featured", MAX_FEATURED_POSTS);
+
+        self.featured.push(post_id);
+    }
+
+    pub fn unfeature_post(&mut self, post_id: usize) {
+        assert!(self.can_moderate(&env::predecessor_account_id()), "Only owner or moderators can feature posts");
+        assert!(self.featured.contains(&post_id), "Post is not featured");
+
+        self.featured.retain(|&x| x != post_id);
+    }
+
+    // in the order they were featured, deleted and unpublished posts are skipped
+    pub fn get_featured_posts(&self) -> Vec<Post> {
+        self.featured
+            .iter()
+            .filter_map(|post_id| self.posts.get(post_id))
+            .filter(|post| post.is_listed())
+            .collect()
+    }
+
+    pub fn report_post(&mut self, post_id: usize, reason: String) {
+        self.assert_not_paused();
+        assert!(self.post_exists(post_id), "Post does not exist");
+        assert!(!reason.trim().is_empty(), "Reason must not be empty");
+
+        let reporter = env::predecessor_account_id();
+        let mut reports = self.reports.get(&post_id).unwrap_or_default();
+        assert!(
+            !reports.iter().any(|report| report.get_reporter() == reporter),
+            "Post was already reported by this account"
+        );
+
+        reports.push(Report::new(post_id, reporter, reason, env::block_timestamp()));
+        self.reports.insert(&post_id, &reports);
+    }
+
+    // checks the caller, so moderators have to send this as a transaction
+    pub fn get_reports(&self, from_index: usize, limit: usize) -> Vec<Report> {
+        assert!(self.can_moderate(&env::predecessor_account_id()), "Only owner or moderators can view reports");
+
+        self.reports
+            .values()
+            .flatten()
+            .skip(from_index)
+            .take(limit.min(MAX_PAGE_LIMIT))
+            .collect()
+    }
+
+    /// Reported and NSFW-flagged posts, most reported first.
+    /// NSFW posts without reports are only looked up among the newest MAX_SCANNED_POSTS.
+    pub fn get_moderation_queue(&self, from_index: usize, limit: usize) -> Vec<PostView> {
+        assert!(self.can_moderate(&env::predecessor_account_id()), "Only owner or moderators can view the moderation queue");
+
+        let mut queue: Vec<(usize, Post)> = self.reports
+            .iter()
+            .filter_map(|(post_id, reports)| self.posts.get(&post_id).map(|post| (reports.len(), post)))
+            .collect();
+
+        let flagged = (0..self.next_post_id)
+            .rev()
+            .filter_map(|post_id| self.posts.get(&post_id))
+            .take(MAX_SCANNED_POSTS)
+            .filter(|post| post.is_nsfw() && self.reports.get(&post.get_post_id()).is_none())
+            .map(|post| (0, post));
+        queue.extend(flagged);
+
+        queue.sort_by_key(|(report_count, post)| (std::cmp::Reverse(*report_count), post.get_post_id()));
+
+        queue
+            .into_iter()
+            .skip(from_index)
+            .take(limit.min(MAX_PAGE_LIMIT))
+            .map(|(_, post)| PostView::new(post))
+            .collect()
+    }
+
+    pub fn clear_reports(&mut self, post_id: usize) {
+        assert!(self.can_moderate(&env::predecessor_account_id()), "Only owner or moderators can clear reports");
+
+        self.reports.remove(&post_id);
+    }
+
+    /// Counts each account once per post. The first view pays for storing the viewer.
+    #[payable]
+    pub fn record_view(&mut self, post_id: usize) -> u64 {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+
+        let initial_storage_usage = env::storage_usage();
+        if self.post_viewers.insert(&(post_id, env::predecessor_account_id())) {
+            post.add_view();
+            self.posts.insert(&post_id, &post);
+        }
+        self.charge_storage(initial_storage_usage);
+
+        post.get_views()
+    }
+
+    pub fn get_post(&self, post_id: usize) -> Option<Post> {
+        self.posts.get(&post_id)
+    }
+
+    // soft-deleted posts still exist
+    pub fn post_exists(&self, post_id: usize) -> bool {
+        self.posts.get(&post_id).is_some()
+    }
+
+    // false when the post doesn't exist or the comment belongs to another post
+    pub fn comment_exists(&self, post_id: usize, comment_id: usize) -> bool {
+        match self.posts.get(&post_id) {
+            Some(post) => post.get_comments().contains(&comment_id),
+            None => false,
+        }
+    }
+
+    // keeps the input order, missing ids come back as None
+    pub fn get_posts_by_ids(&self, ids: Vec<PostId>) -> Vec<Option<Post>> {
+        assert!(ids.len() <= MAX_PAGE_LIMIT, "At most {} posts can be fetched at once", MAX_PAGE_LIMIT);
+
+        ids.iter().map(|post_id| self.posts.get(post_id)).collect()
+    }
+
+    pub fn get_post_view(&self, post_id: usize) -> PostView {
+        let post = self.require_post(post_id);
+
+        PostView::new(post)
+    }
+
+    pub fn get_post_by_slug(&self, slug: String) -> Option<Post> {
+        self.slugs.get(&slug).and_then(|post_id| self.posts.get(&post_id))
+    }
+
+    pub fn get_posts(&self) -> Vec<Post> {
+        self.posts.values().filter(|post| post.is_listed()).collect()
+    }
+
+    // listed posts only, drafts and soft-deleted posts stay off the public profile
+    pub fn get_user_posts(&self, user_id: AccountId) -> Vec<Post> {
+        let mut posts = Vec::new();
+
+        // users without posts have no entry, return an empty list
+        for post_id in self.user_posts.get(&user_id).unwrap_or_default() {
+            if let Some(post) = self.posts.get(&post_id).filter(|post| post.is_listed()) {
+                posts.push(post);
+            }
+        }
+
+        posts
+    }
+
+    // includes drafts, counts the same posts as get_user_posts
+    pub fn get_posts_count(&self, account_id: AccountId) -> u64 {
+        self.user_posts.get(&account_id).map_or(0, |post_ids| post_ids.len() as u64)
+    }
+
+    /// Sums the engagement of every post of `author`, drafts included.
+    /// Reads each of their posts, so the cost grows with the author's post count.
+    pub fn get_author_summary(&self, author: AccountId) -> AuthorSummary {
+        let mut summary = AuthorSummary {
+            post_count: 0,
+            total_upvotes: 0,
+            total_downvotes: 0,
+            total_comments_received: 0,
+            total_donated_received: U128(0),
+        };
+
+        for post_id in self.user_posts.get(&author).unwrap_or_default() {
+            if let Some(post) = self.posts.get(&post_id) {
+                summary.post_count += 1;
+                summary.total_upvotes += post.get_upvotes().len() as u64;
+                summary.total_downvotes += post.get_downvotes().len() as u64;
+                summary.total_comments_received += post.get_comments().len() as u64;
+                let received: Balance = post
+                    .get_donation_logs()
+                    .iter()
+                    .filter(|donation_log| donation_log.is_near())
+                    .map(|donation_log| donation_log.get_net_amount())
+                    .sum();
+                summary.total_donated_received = U128(summary.total_donated_received.0 + received);
+            }
+        }
+
+        summary
+    }
+
+    /// Every post of `account_id`, drafts and soft-deleted posts included, for the author's dashboard.
+    /// `get_posts_by_author` is the public listing.
+    pub fn get_posts_paginated(&self, account_id: AccountId, from_index: usize, limit: usize) -> Vec<Post> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+        let post_ids = self.user_posts.get(&account_id).unwrap_or_default();
+
+        post_ids
+            .iter()
+            .skip(from_index)
+            .take(limit)
+            .filter_map(|post_id| self.posts.get(post_id))
+            .collect()
+    }
+
+    pub fn get_posts_by_author(&self, author: AccountId, from_index: usize, limit: usize) -> Vec<Post> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+        let post_ids = self.user_posts.get(&author).unwrap_or_default();
+
+        // other accounts don't get to see the author's drafts
+        post_ids
+            .iter()
+            .filter_map(|post_id| self.posts.get(post_id))
+            .filter(|post| post.is_listed())
+            .skip(from_index)
+            .take(limit)
+            .collect()
+    }
+
+    // accounts with at least one post, in no particular order
+    pub fn get_authors(&self, from_index: usize, limit: usize) -> Vec<AccountId> {
+        self.user_posts
+            .keys_as_vector()
+            .iter()
+            .skip(from_index)
+            .take(limit.min(MAX_PAGE_LIMIT))
+            .collect()
+    }
+
+    pub fn get_author_count(&self) -> u64 {
+        self.user_posts.len()
+    }
+
+    pub fn follow(&mut self, account_id: AccountId) {
+        self.assert_not_paused();
+        let follower = env::predecessor_account_id();
+        assert_ne!(follower, account_id, "Cannot follow yourself");
+        assert!(env::is_valid_account_id(account_id.as_bytes()), "Invalid account id");
+
+        let mut following = self.following.get(&follower).unwrap_or_default();
+        assert!(!following.contains(&account_id), "Already following this account");
+
+        following.push(account_id);
+        self.following.insert(&follower, &following);
+    }
+
+    pub fn unfollow(&mut self, account_id: AccountId) {
+        self.assert_not_paused();
+        let follower = env::predecessor_account_id();
+
+        let mut following = self.following.get(&follower).unwrap_or_default();
+        assert!(following.contains(&account_id), "Not following this account");
+
+        following.retain(|x| *x != account_id);
+        self.following.insert(&follower, &following);
+    }
+
+    pub fn get_following(&self, account_id: AccountId) -> Vec<AccountId> {
+        self.following.get(&account_id).unwrap_or_default()
+    }
+
+    pub fn get_following_feed(&self, account_id: AccountId, from_index: usize, limit: usize) -> Vec<Post> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+
+        let mut post_ids: Vec<PostId> = self.get_following(account_id)
+            .iter()
+            .flat_map(|author| self.user_posts.get(author).unwrap_or_default())
+            .collect();
+
+        // newest first, ids grow with time
+        post_ids.sort_unstable_by(|a, b| b.cmp(a));
+
+        post_ids
+            .iter()
+            .take(MAX_SCANNED_POSTS)
+            .filter_map(|post_id| self.posts.get(post_id))
+            .filter(|post| post.is_listed())
+            .skip(from_index)
+            .take(limit)
+            .collect()
+    }
+
+    pub fn bookmark(&mut self, post_id: usize) {
+        self.assert_not_paused();
+        assert!(self.post_exists(post_id), "Post does not exist");
+
+        let account_id = env::predecessor_account_id();
+        let mut bookmarks = self.bookmarks.get(&account_id).unwrap_or_default();
+        assert!(!bookmarks.contains(&post_id), "Post is already bookmarked");
+
+        bookmarks.push(post_id);
+        self.bookmarks.insert(&account_id, &bookmarks);
+    }
+
+    pub fn remove_bookmark(&mut self, post_id: usize) {
+        self.assert_not_paused();
+        let account_id = env::predecessor_account_id();
+        let mut bookmarks = self.bookmarks.get(&account_id).unwrap_or_default();
+        assert!(bookmarks.contains(&post_id), "Post is not bookmarked");
+
+        bookmarks.retain(|&x| x != post_id);
+        self.bookmarks.insert(&account_id, &bookmarks);
+    }
+
+    pub fn get_bookmarks(&self, account_id: AccountId) -> Vec<Post> {
+        // bookmarks of deleted posts are skipped
+        self.bookmarks.get(&account_id)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|post_id| self.posts.get(post_id))
+            .collect()
+    }
+
+    #[payable]
+    pub fn set_profile(&mut self, display_name: String, bio: String, avatar_url: String) {
+        self.assert_not_paused();
+        assert!(display_name.chars().count() <= MAX_DISPLAY_NAME_LENGTH, "Display name must be at most {} characters long", MAX_DISPLAY_NAME_LENGTH);
+        assert!(bio.chars().count() <= MAX_BIO_LENGTH, "Bio must be at most {} characters long", MAX_BIO_LENGTH);
+        let initial_storage_usage = env::storage_usage();
+
+        let profile = Profile::new(display_name.trim().to_string(), bio, avatar_url, env::block_timestamp());
+        self.profiles.insert(&env::predecessor_account_id(), &profile);
+
+        self.charge_storage(initial_storage_usage);
+    }
+
+    pub fn get_profile(&self, account_id: AccountId) -> Option<Profile> {
+        self.profiles.get(&account_id)
+    }
+
+    // blocked accounts can't comment on or reply under any of the author's posts
+    pub fn block_commenter(&mut self, account_id: AccountId) {
+        self.assert_not_paused();
+        let author = env::predecessor_account_id();
+        assert_ne!(author, account_id, "Cannot block yourself");
+        assert!(env::is_valid_account_id(account_id.as_bytes()), "Invalid account id");
+
+        let mut blocked = self.blocked_commenters.get(&author).unwrap_or_default();
+        assert!(!blocked.contains(&account_id), "Account is already blocked");
+
+        blocked.push(account_id);
+        self.blocked_commenters.insert(&author, &blocked);
+    }
+
+    pub fn unblock_commenter(&mut self, account_id: AccountId) {
+        self.assert_not_paused();
+        let author = env::predecessor_account_id();
+
+        let mut blocked = self.blocked_commenters.get(&author).unwrap_or_default();
+        assert!(blocked.contains(&account_id), "Account is not blocked");
+
+        blocked.retain(|x| *x != account_id);
+        if blocked.is_empty() {
+            self.blocked_commenters.remove(&author);
+        } else {
+            self.blocked_commenters.insert(&author, &blocked);
+        }
+    }
+
+    pub fn is_blocked(&self, author: AccountId, account_id: AccountId) -> bool {
+        self.blocked_commenters.get(&author).unwrap_or_default().contains(&account_id)
+    }
+
+    /// NSFW posts are included unless `include_nsfw` is false.
+    /// Posts at or below the hide threshold are left out unless `include_hidden` is set,
+    /// `get_post` still returns them.
+    /// At most MAX_SCANNED_POSTS removed or filtered out posts are skipped past the requested page.
+    pub fn get_all_posts(&self, from_index: usize, limit: usize, include_nsfw: Option<bool>, include_hidden: Option<bool>) -> Vec<Post> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+        let include_nsfw = include_nsfw.unwrap_or(true);
+        let include_hidden = include_hidden.unwrap_or(false);
+
+        // walk ids in order, UnorderedMap reorders values on removal
+        (0..self.next_post_id)
+            .take(from_index.saturating_add(limit).saturating_add(MAX_SCANNED_POSTS))
+            .filter_map(|post_id| self.posts.get(&post_id))
+            .filter(|post| post.is_listed())
+            .filter(|post| include_nsfw || !post.is_nsfw())
+            .filter(|post| include_hidden || !self.is_hidden(post))
+            .skip(from_index)
+            .take(limit)
+            .collect()
+    }
+
+    pub fn get_posts_by_tag(&self, tag: String, from_index: usize, limit: usize) -> Vec<Post> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+        let post_ids = self.tag_posts.get(&tag.trim().to_lowercase()).unwrap_or_default();
+
+        post_ids
+            .iter()
+            .filter_map(|post_id| self.posts.get(post_id))
+            .filter(|post| post.is_listed())
+            .skip(from_index)
+            .take(limit)
+            .collect()
+    }
+
+    // every tag in use, unordered
+    pub fn get_all_tags(&self) -> Vec<String> {
+        self.tag_posts.keys().collect()
+    }
+
+    /// (tag, post count) pairs for a tag cloud, most used tag first.
+    /// Counts come from the tag index, so drafts and hidden posts are included.
+    pub fn get_tag_counts(&self, from_index: usize, limit: usize) -> Vec<(String, u64)> {
+        let mut counts: Vec<(String, u64)> = self.tag_posts
+            .iter()
+            .map(|(tag, post_ids)| (tag, post_ids.len() as u64))
+            .collect();
+        counts.sort_by(|(tag_a, a), (tag_b, b)| b.cmp(a).then(tag_a.cmp(tag_b)));
+
+        counts.into_iter().skip(from_index).take(limit.min(MAX_PAGE_LIMIT)).collect()
+    }
+
+    /// Only the newest MAX_SCANNED_POSTS posts are sorted.
+    pub fn get_posts_sorted(&self, sort: String, from_index: usize, limit: usize) -> Vec<Post> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+        let mut posts: Vec<Post> = (0..self.next_post_id)
+            .rev()
+            .filter_map(|post_id| self.posts.get(&post_id))
+            .take(MAX_SCANNED_POSTS)
+            .filter(|post| post.is_listed())
+            .collect();
+
+        // ids grow with time, so they double as the creation order
+        match sort.as_str() {
+            "newest" => posts.sort_by_key(|post| std::cmp::Reverse(post.get_post_id())),
+            "oldest" => posts.sort_by_key(|post| post.get_post_id()),
+            "top" => posts.sort_by_key(|post| (std::cmp::Reverse(post.get_points()), post.get_post_id())),
+            "donated" => posts.sort_by_key(|post| (std::cmp::Reverse(post.get_total_donation()), post.get_post_id())),
+            _ => panic!("Unknown sort, expected newest, oldest, top or donated"),
+        }
+
+        posts.into_iter().skip(from_index).take(limit).collect()
+    }
+
+    /// Newest posts ranked by engagement decayed by age:
+    ///
+    /// ```text
+    /// score = (net_votes + 2 * comments + donated_near) / (age_hours + 2) ^ 1.5
+    /// ```
+    ///
+    /// Only the newest MAX_SCANNED_POSTS posts are considered.
+    pub fn get_trending(&self, limit: usize, include_hidden: Option<bool>) -> Vec<Post> {
+        let now = env::block_timestamp();
+        let include_hidden = include_hidden.unwrap_or(false);
+        let mut scored: Vec<(f64, Post)> = (0..self.next_post_id)
+            .rev()
+            .filter_map(|post_id| self.posts.get(&post_id))
+            .take(MAX_SCANNED_POSTS)
+            .filter(|post| post.is_listed())
+            .filter(|post| include_hidden || !self.is_hidden(post))
+            .map(|post| (trending_score(&post, now), post))
+            .collect();
+
+        // newer post first on equal scores
+        scored.sort_by(|(a, post_a), (b, post_b)| b.partial_cmp(a).unwrap().then(post_b.get_post_id().cmp(&post_a.get_post_id())));
+
+        scored.into_iter().take(limit.min(MAX_PAGE_LIMIT)).map(|(_, post)| post).collect()
+    }
+
+    /// A listed post picked with the block's random seed. Every call within the same
+    /// block returns the same post, so this is for "surprise me" links, not for anything with stakes.
+    pub fn get_random_post(&self) -> Option<Post> {
+        let posts = self.posts.values_as_vector();
+        if posts.is_empty() {
+            return None;
+        }
+
+        let seed = env::random_seed().iter().take(8).fold(0u64, |acc, byte| acc << 8 | u64::from(*byte));
+        let start = seed % posts.len();
+
+        // walk on from the picked slot until a listed post turns up
+        (0..posts.len().min(MAX_SCANNED_POSTS as u64))
+            .filter_map(|offset| posts.get((start + offset) % posts.len()))
+            .find(|post| post.is_listed())
+    }
+
+    pub fn search_posts(&self, query: String, from_index: usize, limit: usize) -> Vec<Post> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+        let query = query.to_lowercase();
+
+        // scans the newest MAX_SCANNED_POSTS posts, older matches are not found
+        (0..self.next_post_id)
+            .rev()
+            .filter_map(|post_id| self.posts.get(&post_id))
+            .take(MAX_SCANNED_POSTS)
+            .filter(|post| post.is_listed())
+            .filter(|post| post.get_title().to_lowercase().contains(&query) || post.get_body().to_lowercase().contains(&query))
+            .skip(from_index)
+            .take(limit)
+            .collect()
+    }
+
+    /// Published posts created in [start, end), timestamps in nanoseconds.
+    /// At most MAX_SCANNED_POSTS removed or unlisted posts are skipped past the requested page.
+    pub fn get_posts_by_date_range(&self, start: u64, end: u64, from_index: usize, limit: usize) -> Vec<Post> {
+        assert!(start <= end, "Start must not be after end");
+        let limit = limit.min(MAX_PAGE_LIMIT);
+
+        // ids grow with time, so the walk starts at the range and stops at the first post past it
+        (self.first_post_id_at(start)..self.next_post_id)
+            .take(from_index.saturating_add(limit).saturating_add(MAX_SCANNED_POSTS))
+            .filter_map(|post_id| self.posts.get(&post_id))
+            .take_while(|post| post.get_created_at() < end)
+            .filter(|post| post.is_listed())
+            .skip(from_index)
+            .take(limit)
+            .collect()
+    }
+
+    /// Listed posts with an id strictly greater than `after_post_id`, oldest first.
+    /// Ids start at 0, so an indexer passes the last id it has seen; deleted ids are skipped.
+    pub fn get_posts_since(&self, after_post_id: usize, limit: usize) -> Vec<Post> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+
+        (after_post_id.saturating_add(1)..self.next_post_id)
+            .filter_map(|post_id| self.posts.get(&post_id))
+            .filter(|post| post.is_listed())
+            .take(limit)
+            .collect()
+    }
+
+    pub fn get_paging_posts(&self, page: usize, page_size: usize) -> Vec<Post> {
+        assert!(page_size > 0, "Page size must be greater than 0");
+        assert!(page > 0, "Page must be greater than 0");
+
+        //notice: page start from 1
+        let start = (page - 1) * page_size;
+
+        self.posts
+            .values()
+            .filter(|post| post.is_listed())
+            .skip(start)
+            .take(page_size)
+            .collect()
+    }
+
+    pub fn get_total_posts(&self) -> u64 {
+        self.posts.len()
+    }
+
+    /// The replaced version is kept in the post history, the deposit pays for storing it.
+    #[payable]
+    pub fn edit_post(&mut self, post_id: usize, title: String, body: String) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+        assert_eq!(post.get_author(), env::predecessor_account_id(), "Only author can edit the post");
+        assert_valid_post(&title, &body);
+        self.assert_allowed_content(&title);
+        self.assert_allowed_content(&body);
+        let initial_storage_usage = env::storage_usage();
+
+        post.edit(title, body, env::block_timestamp());
+        self.posts.insert(&post_id, &post);
+
+        self.charge_storage(initial_storage_usage);
+    }
+
+    // previous versions of the post, oldest first
+    pub fn get_post_history(&self, post_id: usize) -> Vec<PostRevision> {
+        self.require_post(post_id).get_revisions()
+    }
+
+    /// Posts with donations keep their record unless `force` is set, use `soft_delete_post` for those.
+    pub fn delete_post(&mut self, post_id: usize, force: Option<bool>) {
+        assert!(self.can_moderate(&env::predecessor_account_id()), "Only owner or moderators can delete posts");
+        if !force.unwrap_or(false) {
+            self.assert_no_donations(post_id);
+        }
+
+        self.remove_post(post_id);
+    }
+
+    /// Removes every post of a banned author, returns how many were removed.
+    /// Like `delete_post`, nothing is removed if any of the posts has donations unless `force` is set.
+    pub fn purge_author(&mut self, author: AccountId, force: Option<bool>) -> u64 {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can purge an author");
+
+        // state from before remove_post kept user_posts in sync can list ids that are gone
+        let post_ids: Vec<PostId> = self.user_posts
+            .get(&author)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|post_id| self.posts.get(post_id).is_some())
+            .collect();
+        if !force.unwrap_or(false) {
+            for post_id in post_ids.iter() {
+                self.assert_no_donations(*post_id);
+            }
+        }
+        for post_id in post_ids.iter() {
+            self.remove_post(*post_id);
+        }
+        self.user_posts.remove(&author);
+
+        post_ids.len() as u64
+    }
+
+    /// Flags the post as NSFW or clears the flag.
+    pub fn set_nsfw(&mut self, post_id: usize, nsfw: bool) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+        let caller = env::predecessor_account_id();
+        assert!(post.get_author() == caller || self.can_moderate(&caller), "Only author, owner or moderators can flag the post");
+
+        post.set_nsfw(nsfw);
+        self.posts.insert(&post_id, &post);
+    }
+
+    /// Hides the post from feeds and search but keeps the record, `get_post` still returns it.
+    pub fn soft_delete_post(&mut self, post_id: usize) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+        let caller = env::predecessor_account_id();
+        assert!(post.get_author() == caller || self.can_moderate(&caller), "Only author, owner or moderators can delete the post");
+        assert!(!post.is_deleted(), "Post is already deleted");
+
+        post.soft_delete();
+        self.posts.insert(&post_id, &post);
+    }
+
+    pub fn restore_post(&mut self, post_id: usize) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+        let caller = env::predecessor_account_id();
+        assert!(post.get_author() == caller || self.can_moderate(&caller), "Only author, owner or moderators can restore the post");
+        assert!(post.is_deleted(), "Post is not deleted");
+
+        post.restore();
+        self.posts.insert(&post_id, &post);
+    }
+
+    pub fn delete_my_post(&mut self, post_id: usize) {
+        self.assert_not_paused();
+        let post = self.require_post(post_id);
+        assert_eq!(post.get_author(), env::predecessor_account_id(), "Only author can delete the post");
+        self.assert_no_donations(post_id);
+
+        self.remove_post(post_id);
+    }
+
+    #[payable]
+    pub fn create_comment(&mut self, post_id: usize, body: String) -> usize {
+        self.add_comment(post_id, body, None)
+    }
+
+    #[payable]
+    pub fn reply(&mut self, post_id: usize, parent_comment_id: usize, body: String) -> usize {
+        self.add_comment(post_id, body, Some(parent_comment_id))
+    }
+
+    pub fn get_replies(&self, post_id: usize, parent_comment_id: usize) -> Vec<Comment> {
+        self.get_comments(post_id)
+            .into_iter()
+            .filter(|comment| comment.get_parent_comment_id() == Some(parent_comment_id))
+            .collect()
+    }
+
+    pub fn upvote_comment(&mut self, post_id: usize, comment_id: usize) -> usize {
+        self.assert_not_paused();
+        let post = self.require_live_post(post_id);
+        assert!(post.get_comments().contains(&comment_id), "Comment does not exist");
+
+        let mut comment = self.comments.get(&comment_id).unwrap();
+        comment.toggle_upvote(env::predecessor_account_id());
+        self.comments.insert(&comment_id, &comment);
+
+        comment.get_upvotes().len()
+    }
+
+    pub fn get_comments_sorted_by_votes(&self, post_id: usize) -> Vec<Comment> {
+        let mut comments = self.get_comments(post_id);
+
+        // most upvoted first, ties keep the oldest first
+        comments.sort_by_key(|comment| std::cmp::Reverse(comment.get_upvotes().len()));
+        comments
+    }
+
+    pub fn edit_comment(&mut self, post_id: usize, comment_id: usize, body: String) -> Comment {
+        self.assert_not_paused();
+        let post = self.require_post(post_id);
+        assert!(post.get_comments().contains(&comment_id), "Comment does not exist");
+
+        let mut comment = self.comments.get(&comment_id).unwrap();
+        assert_eq!(comment.get_author(), env::predecessor_account_id(), "Only author can edit the comment");
+        self.assert_valid_comment(&body);
+
+        comment.edit(body, env::block_timestamp());
+        self.comments.insert(&comment_id, &comment);
+
+        comment
+    }
+
+    pub fn delete_comment(&mut self, post_id: usize, comment_id: usize) {
+        // only owner or moderators can delete comments
+        assert!(self.can_moderate(&env::predecessor_account_id()), "Only owner or moderators can delete comments");
+
+        // Check if the post exists
+        let mut post = self.require_post(post_id);
+
+        // look the comment up by id, not by its position in the post
+        assert!(post.remove_comment(comment_id), "Comment does not exist");
+
+        self.posts.insert(&post_id, &post);
+        self.remove_comment_record(comment_id);
+    }
+
+    pub fn delete_my_comment(&mut self, post_id: usize, comment_id: usize) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+        let comment = match self.comments.get(&comment_id) {
+            Some(comment) => comment,
+            None => panic!("Comment does not exist"),
+        };
+        assert_eq!(comment.get_author(), env::predecessor_account_id(), "Only author can delete the comment");
+
+        // look the comment up by id, not by its position in the post
+        assert!(post.remove_comment(comment_id), "Comment does not exist");
+
+        self.posts.insert(&post_id, &post);
+        self.remove_comment_record(comment_id);
+    }
+
+    #[payable]
+    pub fn donate(&mut self, post_id: usize, message: String) {
+        self.assert_not_paused();
+
+        // the donation is whatever the donor attached
+        self.record_donation(post_id, env::predecessor_account_id(), env::attached_deposit(), message, None, None);
+    }
+
+    /// Splits the attached deposit across several posts, one donation log per post.
+    /// The allocations must add up to exactly the attached deposit.
+    #[payable]
+    pub fn donate_split(&mut self, allocations: Vec<(PostId, U128)>, message: String) {
+        self.assert_not_paused();
+        assert!(!allocations.is_empty(), "Allocations must not be empty");
+        assert!(allocations.len() <= MAX_DONATION_SPLITS, "Donations can be split across at most {} posts", MAX_DONATION_SPLITS);
+
+        let total: u128 = allocations.iter().map(|(_, amount)| amount.0).sum();
+        assert_eq!(total, env::attached_deposit(), "Allocations must add up to the attached deposit");
+        let post_ids: HashSet<PostId> = allocations.iter().map(|(post_id, _)| *post_id).collect();
+        assert_eq!(post_ids.len(), allocations.len(), "Each post can appear only once in the allocations");
+
+        for (post_id, amount) in allocations {
+            self.record_donation(post_id, env::predecessor_account_id(), amount.0, message.clone(), None, None);
+        }
+    }
+
+    /// Sets the NFT contract whose holders can donate to the caller's posts with `donate_as_holder`, None clears it.
+    #[payable]
+    pub fn set_holder_contract(&mut self, nft_contract: Option<AccountId>) {
+        self.assert_not_paused();
+        let initial_storage_usage = env::storage_usage();
+        let author = env::predecessor_account_id();
+
+        match nft_contract {
+            Some(nft_contract) => {
+                assert!(env::is_valid_account_id(nft_contract.as_bytes()), "Invalid NFT contract");
+                self.holder_contracts.insert(&author, &nft_contract);
+            },
+            None => {
+                self.holder_contracts.remove(&author);
+            },
+        }
+
+        self.charge_storage(initial_storage_usage);
+    }
+
+    pub fn get_holder_contract(&self, author: AccountId) -> Option<AccountId> {
+        self.holder_contracts.get(&author)
+    }
+
+    /// Donates like `donate`, but first asks the NFT contract the post's author set
+    /// whether the caller owns `token_id`. Holder donations record that contract on their log,
+    /// if the check fails the deposit is refunded.
+    #[payable]
+    pub fn donate_as_holder(&mut self, post_id: usize, token_id: String, message: String) -> Promise {
+        self.assert_not_paused();
+        let post = self.require_live_post(post_id);
+        let nft_contract = match self.holder_contracts.get(&post.get_author()) {
+            Some(nft_contract) => nft_contract,
+            None => panic!("The author has not set an NFT contract"),
+        };
+
+        // checked again in the callback, failing there refunds instead of panicking
+        let amount = env::attached_deposit();
+        self.assert_donation_allowed(post_id, &env::predecessor_account_id(), amount, &None);
+
+        let donation = HolderDonation {
+            post_id,
+            donor: env::predecessor_account_id(),
+            amount: U128(amount),
+            message,
+            nft_contract: nft_contract.clone(),
+        };
+        ext_nft::nft_token(token_id, &nft_contract, 0, GAS_FOR_NFT_TOKEN).then(ext_self::on_holder_verified(
+            donation,
+            &env::current_account_id(),
+            0,
+            GAS_FOR_HOLDER_CALLBACK,
+        ))
+    }
+
+    /// Records the holder donation when the NFT is owned by the donor, refunds it otherwise.
+    #[private]
+    pub fn on_holder_verified(&mut self, donation: HolderDonation) -> bool {
+        assert_eq!(env::promise_results_count(), 1, "Expected one promise result");
+        let HolderDonation { post_id, donor, amount, message, nft_contract } = donation;
+
+        let token: Option<NftToken> = match env::promise_result(0) {
+            PromiseResult::Successful(result) => near_sdk::serde_json::from_slice(&result).unwrap_or(None),
+            _ => None,
+        };
+        let is_holder = token.is_some_and(|token| token.owner_id == donor);
+
+        // the post, the author's NFT contract, the minimum or the cooldown may have changed
+        // while the check was running, a panic here would keep the deposit without crediting anyone
+        let is_accepted_contract = self.posts
+            .get(&post_id)
+            .is_some_and(|post| !post.is_deleted() && self.holder_contracts.get(&post.get_author()) == Some(nft_contract.clone()));
+        if !is_holder || !is_accepted_contract || !self.is_donation_allowed(post_id, &donor, amount.0, &None) {
+            Promise::new(donor).transfer(amount.0);
+            return false;
+        }
+
+        self.record_donation(post_id, donor, amount.0, message, None, Some(nft_contract));
+        true
+    }
+
+    /// Tips the comment's author with the attached deposit, same minimum, cooldown and fee as post donations.
+    /// The storage of the tip's log is taken out of the amount the author receives.
+    #[payable]
+    pub fn donate_to_comment(&mut self, post_id: usize, comment_id: usize, message: String) {
+        self.assert_not_paused();
+        self.require_live_post(post_id);
+        let comment = match self.get_comment(post_id, comment_id) {
+            Some(comment) => comment,
+            None => panic!("Comment does not exist"),
+        };
+
+        let amount = env::attached_deposit();
+        let donor = env::predecessor_account_id();
+        let initial_storage_usage = env::storage_usage();
+        let donation_log = self.new_donation_log(post_id, &donor, amount, message, None);
+        self.donation_comments.insert(&donation_log.get_donation_id(), &comment_id);
+
+        let donated = self.donor_totals.get(&donor).unwrap_or(0);
+        self.donor_totals.insert(&donor, &(donated + amount));
+
+        let mut donation_logs = self.comment_donations.get(&comment_id).unwrap_or_default();
+        donation_logs.push(donation_log);
+        self.comment_donations.insert(&comment_id, &donation_logs);
+
+        // unlike a post, the comment's record doesn't hold the log, so the tip pays for the bytes it added
+        let storage_cost = Balance::from(env::storage_usage().saturating_sub(initial_storage_usage)) * env::storage_byte_cost();
+        let donation_log = donation_logs.last_mut().unwrap();
+        assert!(donation_log.get_net_amount() >= storage_cost, "Donation must cover the storage cost of {} yoctoNEAR", storage_cost);
+        donation_log.deduct_storage(storage_cost);
+        let net_amount = donation_log.get_net_amount();
+        self.comment_donations.insert(&comment_id, &donation_logs);
+
+        let paid = self.storage_paid.get(&donor).unwrap_or(0);
+        self.storage_paid.insert(&donor, &(paid + storage_cost));
+
+        let author = comment.get_author();
+        let balance = self.pending_donations.get(&author).unwrap_or(0);
+        self.pending_donations.insert(&author, &(balance + net_amount));
+
+        log_event("comment_donation_received", json!({
+            "post_id": post_id,
+            "comment_id": comment_id,
+            "donor": donor,
+            "author": author,
+            "amount": amount.to_string(),
+        }));
+    }
+
+    pub fn get_comment_donations(&self, post_id: usize, comment_id: usize) -> Vec<DonationLog> {
+        if self.get_comment(post_id, comment_id).is_none() {
+            return Vec::new();
+        }
+
+        self.comment_donations.get(&comment_id).unwrap_or_default()
+    }
+
+    /// Lets `ft_on_transfer` take donations from the `token` contract, `min_amount` is in the token's units.
+    pub fn add_accepted_token(&mut self, token: AccountId, min_amount: U128) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can accept tokens");
+        assert!(env::is_valid_account_id(token.as_bytes()), "Invalid account id");
+        assert!(min_amount.0 > 0, "Minimum amount must be greater than 0");
+
+        self.accepted_tokens.insert(&token, &min_amount.0);
+    }
+
+    // pending balances in the token can still be withdrawn
+    pub fn remove_accepted_token(&mut self, token: AccountId) {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can remove tokens");
+
+        self.accepted_tokens.remove(&token);
+    }
+
+    pub fn get_accepted_tokens(&self) -> Vec<(AccountId, U128)> {
+        self.accepted_tokens.iter().map(|(token, min_amount)| (token, U128(min_amount))).collect()
+    }
+
+    /// NEP-141 receiver, called by the token contract on `ft_transfer_call`.
+    /// `msg` is JSON like `{"post_id": 0, "message": "Thanks!"}`. Panicking makes
+    /// the token contract refund the transfer. Only accepted token contracts may call it,
+    /// anyone else could pass a made up `sender_id` and amount.
+    pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> U128 {
+        self.assert_not_paused();
+        let token = env::predecessor_account_id();
+        let min_amount = match self.accepted_tokens.get(&token) {
+            Some(min_amount) => min_amount,
+            None => panic!("Token is not accepted"),
+        };
+        assert!(amount.0 >= min_amount, "Donation must be at least {} of this token", min_amount);
+        let ft_donation: FtDonationMsg = near_sdk::serde_json::from_str(&msg).expect("Invalid msg, expected {\"post_id\": ..., \"message\": ...}");
+
+        self.record_donation(ft_donation.post_id, sender_id, amount.0, ft_donation.message.unwrap_or_default(), Some(token), None);
+
+        // the whole amount is kept
+        U128(0)
+    }
+
+    pub fn get_pending_ft_donations(&self, account_id: AccountId, token: AccountId) -> U128 {
+        U128(self.pending_ft_donations.get(&(account_id, token)).unwrap_or(0))
+    }
+
+    /// Sends the caller's pending donations in `token_id` with `ft_transfer`.
+    /// The caller has to be registered with the token contract.
+    pub fn withdraw_ft_donations(&mut self, token_id: AccountId) -> Promise {
+        self.assert_not_paused();
+        let author = env::predecessor_account_id();
+        let amount = self.pending_ft_donations.remove(&(author.clone(), token_id.clone())).unwrap_or(0);
+        assert!(amount > 0, "No donations to withdraw");
+
+        // ft_transfer requires exactly one yoctoNEAR
+        ext_ft::ft_transfer(author.clone(), U128(amount), None, &token_id, 1, GAS_FOR_FT_TRANSFER).then(ext_self::on_ft_withdraw_complete(
+            author,
+            token_id,
+            U128(amount),
+            &env::current_account_id(),
+            0,
+            GAS_FOR_WITHDRAW_CALLBACK,
+        ))
+    }
+
+    #[private]
+    pub fn on_ft_withdraw_complete(&mut self, author: AccountId, token: AccountId, amount: U128) -> bool {
+        assert_eq!(env::promise_results_count(), 1, "Expected one promise result");
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => true,
+            _ => {
+                // the transfer failed, give the author their balance back
+                let key = (author, token);
+                let balance = self.pending_ft_donations.get(&key).unwrap_or(0);
+                self.pending_ft_donations.insert(&key, &(balance + amount.0));
+                false
+            }
+        }
+    }
+
+    pub fn redact_donation_message(&mut self, post_id: usize, donation_id: usize) {
+        assert!(self.can_moderate(&env::predecessor_account_id()), "Only owner or moderators can redact donations");
+
+        let mut post = self.require_post(post_id);
+        assert!(post.redact_donation_message(donation_id), "Donation does not exist");
+
+        self.posts.insert(&post_id, &post);
+    }
+
+    pub fn get_top_donors(&self, post_id: usize, limit: usize) -> Vec<(AccountId, U128)> {
+        let post = self.require_post(post_id);
+
+        post.get_donor_totals()
+            .into_iter()
+            .take(limit.min(MAX_PAGE_LIMIT))
+            .map(|(donor, total)| (donor, U128(total)))
+            .collect()
+    }
+
+    // lifetime totals across all posts, biggest donor first
+    pub fn get_top_donors_global(&self, limit: usize) -> Vec<(AccountId, U128)> {
+        let mut totals = self.donor_totals.to_vec();
+        totals.sort_by_key(|(_, total)| std::cmp::Reverse(*total));
+
+        totals
+            .into_iter()
+            .take(limit.min(MAX_PAGE_LIMIT))
+            .map(|(donor, total)| (donor, U128(total)))
+            .collect()
+    }
+
+    // oldest first, lets the frontend lazy-load the donation history
+    pub fn get_donations(&self, post_id: usize, from_index: usize, limit: usize) -> Vec<DonationLog> {
+        let post = self.require_post(post_id);
+
+        post.get_donation_logs()
+            .into_iter()
+            .skip(from_index)
+            .take(limit.min(MAX_PAGE_LIMIT))
+            .collect()
+    }
+
+    /// Looks a post donation or comment tip up by id, None for unknown ids.
+    pub fn get_donation(&self, donation_id: usize) -> Option<DonationLog> {
+        if let Some(comment_id) = self.donation_comments.get(&donation_id) {
+            return self.comment_donations
+                .get(&comment_id)?
+                .into_iter()
+                .find(|donation_log| donation_log.get_donation_id() == donation_id);
+        }
+
+        let post_id = self.donation_posts.get(&donation_id)?;
+        self.posts.get(&post_id)?.get_donation_log(donation_id)
+    }
+
+    pub fn get_donation_count(&self, post_id: usize) -> u64 {
+        self.require_post(post_id).get_donation_count()
+    }
+
+    pub fn withdraw_donations(&mut self) -> Promise {
+        self.assert_not_paused();
+        let author = env::predecessor_account_id();
+        let amount = self.pending_donations.remove(&author).unwrap_or(0);
+        assert!(amount > 0, "No donations to withdraw");
+
+        Promise::new(author.clone()).transfer(amount).then(ext_self::on_withdraw_complete(
+            author,
+            U128(amount),
+            &env::current_account_id(),
+            0,
+            GAS_FOR_WITHDRAW_CALLBACK,
+        ))
+    }
+
+    #[private]
+    pub fn on_withdraw_complete(&mut self, author: AccountId, amount: U128) -> bool {
+        assert_eq!(env::promise_results_count(), 1, "Expected one promise result");
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => true,
+            _ => {
+                // the transfer failed, give the author their balance back
+                let balance = self.pending_donations.get(&author).unwrap_or(0);
+                self.pending_donations.insert(&author, &(balance + amount.0));
+                false
+            }
+        }
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> U128 {
+        U128(self.storage_paid.get(&account_id).unwrap_or(0))
+    }
+
+    pub fn get_pending_donations(&self, account_id: AccountId) -> U128 {
+        U128(self.pending_donations.get(&account_id).unwrap_or(0))
+    }
+
+    pub fn get_contract_balance(&self) -> U128 {
+        U128(env::account_balance())
+    }
+
+    // donations credited to authors that haven't been withdrawn yet
+    pub fn get_total_pending_donations(&self) -> U128 {
+        U128(self.pending_donations.values().sum())
+    }
+
+    pub fn get_stats(&self) -> BlogStats {
+        BlogStats {
+            total_posts: self.posts.len(),
+            total_comments: self.comments.len(),
+            total_donations: self.total_donations,
+            next_post_id: self.next_post_id,
+            // only accounts with at least one post have an entry
+            total_authors: self.user_posts.len(),
+        }
+    }
+
+    /// Rebuilds the derived state from `posts`: the per-author post lists, the donation
+    /// counter and the comment records, dropping comments of posts that no longer exist.
+    /// Reads every post, so this is only practical while the blog is small.
+    pub fn recompute_totals(&mut self) -> BlogStats {
+        assert_eq!(self.owner, env::predecessor_account_id(), "Only owner can recompute totals");
+
+        let mut posts = self.posts.to_vec();
+        posts.sort_by_key(|(post_id, _)| *post_id);
+
+        self.user_posts.clear();
+        self.total_donations = 0;
+        let mut comment_ids = HashSet::new();
+        for (post_id, post) in posts.iter() {
+            let mut user_posts = self.user_posts.get(&post.get_author()).unwrap_or_default();
+            user_posts.push(*post_id);
+            self.user_posts.insert(&post.get_author(), &user_posts);
+
+            self.total_donations += post.get_donation_count();
+            comment_ids.extend(post.get_comments());
+        }
+        for comment_id in comment_ids.iter() {
+            self.total_donations += self.comment_donations.get(comment_id).map_or(0, |donation_logs| donation_logs.len() as u64);
+        }
+
+        let orphaned_comments: Vec<CommentId> = self.comments.keys().filter(|comment_id| !comment_ids.contains(comment_id)).collect();
+        for comment_id in orphaned_comments {
+            self.remove_comment_record(comment_id);
+        }
+
+        self.get_stats()
+    }
+
+    pub fn get_next_post_id(&self) -> usize {
+        self.next_post_id
+    }
+
+    pub fn get_next_comment_id(&self) -> usize {
+        self.next_comment_id
+    }
+
+    pub fn get_next_donation_id(&self) -> usize {
+        self.next_donation_id
+    }
+
+    pub fn get_total_donations(&self) -> u64 {
+        self.total_donations
+    }
+
+    pub fn get_comments(&self, post_id: usize) -> Vec<Comment> {
+        let post = self.require_post(post_id);
+        
+        let mut comments = Vec::new();
+        for comment_id in post.get_comments_pinned_first() {
+            comments.push(self.comments.get(&comment_id).unwrap());
+        }
+        comments
+    }
+
+    pub fn get_comments_paginated(&self, post_id: usize, from_index: usize, limit: usize) -> Vec<Comment> {
+        let post = self.require_post(post_id);
+        let limit = limit.min(MAX_PAGE_LIMIT);
+
+        // the pinned comment takes the first slot of the first page
+        post.get_comments_pinned_first()
+            .iter()
+            .skip(from_index)
+            .take(limit)
+            .filter_map(|comment_id| self.comments.get(comment_id))
+            .collect()
+    }
+
+    /// Everything a post page needs in one call: the post and a page of its comments.
+    pub fn get_post_page(&self, post_id: usize, comment_from: usize, comment_limit: usize) -> PostPage {
+        let mut post = self.require_post(post_id);
+        let comment_ids = post.get_comments_pinned_first();
+        post.take_comments();
+
+        PostPage {
+            post,
+            comments: comment_ids
+                .iter()
+                .skip(comment_from)
+                .take(comment_limit.min(MAX_PAGE_LIMIT))
+                .filter_map(|comment_id| self.comments.get(comment_id))
+                .collect(),
+            comment_count: comment_ids.len() as u64,
+        }
+    }
+
+    /// (post_id, comment) pairs across all posts, newest comment first.
+    /// Comments of deleted posts are skipped.
+    pub fn get_recent_comments(&self, from_index: usize, limit: usize) -> Vec<(PostId, Comment)> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+
+        (0..self.next_comment_id)
+            .rev()
+            .filter_map(|comment_id| {
+                let post_id = self.comment_posts.get(&comment_id)?;
+                self.posts.get(&post_id)?;
+                Some((post_id, self.comments.get(&comment_id)?))
+            })
+            .skip(from_index)
+            .take(limit)
+            .collect()
+    }
+
+    /// (post_id, comment) pairs written by `author`, oldest first.
+    /// Comments of deleted posts are skipped.
+    pub fn get_comments_by_author(&self, author: AccountId, from_index: usize, limit: usize) -> Vec<(PostId, Comment)> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+
+        self.author_comments.get(&author)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|comment_id| {
+                let post_id = self.comment_posts.get(comment_id)?;
+                self.posts.get(&post_id)?;
+                Some((post_id, self.comments.get(comment_id)?))
+            })
+            .skip(from_index)
+            .take(limit)
+            .collect()
+    }
+
+    pub fn get_notifications(&self, account_id: AccountId) -> Vec<Notification> {
+        self.notifications.get(&account_id).unwrap_or_default()
+    }
+
+    pub fn clear_notifications(&mut self) {
+        self.notifications.remove(&env::predecessor_account_id());
+    }
+
+    pub fn get_paging_comments(&self, post_id: usize, page: usize, page_size: usize) -> Vec<Comment> {
+        assert!(page_size > 0, "Page size must be greater than 0");
+        assert!(page > 0, "Page must be greater than 0");
+
+        //notice: page start from 1
+        self.get_comments_paginated(post_id, (page - 1) * page_size, page_size)
+    }
+
+    pub fn get_total_comments(&self) -> u64 {
+        self.comments.len()
+    }
+
+    // None when either the post or the comment doesn't exist
+    pub fn get_comment(&self, post_id: usize, comment_id: usize) -> Option<Comment> {
+        let post = self.posts.get(&post_id)?;
+        if !post.get_comments().contains(&comment_id) {
+            return None;
+        }
+
+        self.comments.get(&comment_id)
+    }
+
+    pub fn get_post_total_comments(&self, post_id: usize) -> u64 {
+        let post = self.require_post(post_id);
+        
+        post.get_comments().len().try_into().unwrap()
+    }
+
+    pub fn upvote(&mut self, post_id: usize) -> i64 {
+        self.assert_not_paused();
+        let mut post = self.require_live_post(post_id);
+
+        // upvoting twice removes the upvote
+        let voter = env::predecessor_account_id();
+        if !post.add_upvote(voter.clone()) {
+            post.remove_upvote(voter);
+        }
+
+        self.posts.insert(&post_id, &post);
+        post.get_points()
+    }
+
+    /// Sets the caller's vote: 1 upvotes, -1 downvotes, 0 clears it.
+    /// Unlike `upvote`/`downvote` this doesn't toggle, repeating a vote changes nothing.
+    pub fn vote(&mut self, post_id: usize, direction: i8) -> i64 {
+        self.assert_not_paused();
+        let mut post = self.require_live_post(post_id);
+
+        let voter = env::predecessor_account_id();
+        match direction {
+            1 => { post.add_upvote(voter); },
+            -1 => { post.add_downvote(voter); },
+            0 => {
+                post.remove_upvote(voter.clone());
+                post.remove_downvote(voter);
+            },
+            _ => panic!("Direction must be -1, 0 or 1"),
+        }
+
+        self.posts.insert(&post_id, &post);
+        post.get_points()
+    }
+
+    pub fn react(&mut self, post_id: usize, reaction: String) {
+        self.assert_not_paused();
+        assert!(REACTIONS.contains(&reaction.as_str()), "Reaction must be one of {}", REACTIONS.join(", "));
+        let mut post = self.require_live_post(post_id);
+
+        post.toggle_reaction(env::predecessor_account_id(), reaction);
+        self.posts.insert(&post_id, &post);
+    }
+
+    // every allowed reaction with its count, in a fixed order
+    pub fn get_reactions(&self, post_id: usize) -> Vec<(String, u64)> {
+        let post = self.require_post(post_id);
+
+        REACTIONS
+            .iter()
+            .map(|reaction| (reaction.to_string(), post.count_reactions(reaction)))
+            .collect()
+    }
+
+    pub fn remove_upvote(&mut self, post_id: usize) {
+        self.assert_not_paused();
+        let mut post = self.require_post(post_id);
+
+        let voter = env::predecessor_account_id(); 
+        post.remove_upvote(voter);
+        
+        self.posts.insert(&post_id, &post);
+    }
+
+    pub fn downvote(&mut self, post_id: usize) -> i64 {
+        self.assert_not_paused();
+        let mut post = self.require_live_post(post_id);
+
+        // downvoting twice removes the downvote
+        let voter = env::predecessor_account_id();
+        if !post.add_downvote(voter.clone()) {
+            post.remove_downvote(voter);
+        }
+
+        self.posts.insert(&post_id, &post);
+        post.get_points()
+    }
+
+    pub fn remove_downvote(&mut self, post_id: usize) {
+        self.assert_not_paused();
+        let mut post =  self.require_post(post_id);        
+        let voter = env::predecessor_account_id(); 
+        post.remove_downvote(voter);
+        
+        self.posts.insert(&post_id, &post);
+    }
+
+    pub fn get_votes_statistics(&self, post_id: usize) -> (usize, usize) {
+        let post =  self.require_post(post_id);         
+        let upvotes = post.get_upvotes().len();
+        let downvotes = post.get_downvotes().len();
+
+        (upvotes, downvotes)
+    }
+
+    pub fn get_user_vote_status(&self, post_id: usize, user_id: AccountId) -> VoteStatus {
+        let post =  self.require_post(post_id);        
+        let voter = user_id;
+        let upvotes = post.get_upvotes().contains(&voter);
+        let downvotes = post.get_downvotes().contains(&voter);
+
+        if upvotes && !downvotes {
+            VoteStatus::Upvoted
+        } else if !upvotes && downvotes {
+            VoteStatus::Downvoted
+        } else {
+            VoteStatus::None
+        }
+    }
+
+    pub fn get_user_vote(&self, post_id: usize, account_id: AccountId) -> i8 {
+        match self.get_user_vote_status(post_id, account_id) {
+            VoteStatus::Upvoted => 1,
+            VoteStatus::Downvoted => -1,
+            VoteStatus::None => 0,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_post(&mut self, title: String, body: String, tags: Option<Vec<String>>, nsfw: bool, cover_image_url: String, excerpt: String, published: bool, publish_at: Option<u64>) -> usize {
+        self.assert_can_post();
+        self.assert_valid_new_post(&title, &body);
+        assert_valid_post_extras(&cover_image_url, &excerpt);
+        let initial_storage_usage = env::storage_usage();
+
+        let post_id = self.insert_post(title, body, tags, nsfw, cover_image_url, excerpt, published, publish_at);
+
+        self.charge_storage(initial_storage_usage);
+
+        post_id
+    }
+
+    // every way of creating posts runs these, per call
+    fn assert_can_post(&self) {
+        self.assert_not_paused();
+        self.assert_allowed_author();
+        self.assert_post_cooldown();
+    }
+
+    // and these per post
+    fn assert_valid_new_post(&self, title: &str, body: &str) {
+        assert_valid_post(title, body);
+        self.assert_allowed_content(title);
+        self.assert_allowed_content(body);
+    }
+
+    // writes the post and its indexes, callers run assert_can_post and charge for storage
+    #[allow(clippy::too_many_arguments)]
+    fn insert_post(&mut self, title: String, body: String, tags: Option<Vec<String>>, nsfw: bool, cover_image_url: String, excerpt: String, published: bool, publish_at: Option<u64>) -> usize {
+        let tags = normalize_tags(tags.unwrap_or_default());
+        let post_id = self.next_post_id;
+
+        let mut post =  Post::new(post_id, title, body, env::predecessor_account_id(), env::block_timestamp(), tags.clone(), published);
+        post.set_nsfw(nsfw);
+        post.set_cover_image_url(cover_image_url);
+        if !excerpt.trim().is_empty() {
+            post.set_excerpt(excerpt.trim().to_string());
+        }
+        if let Some(publish_at) = publish_at {
+            post.set_publish_at(publish_at);
+            self.scheduled_posts.insert(&post_id);
+        }
+        self.assign_slug(&mut post);
+        
+        self.posts.insert(&post_id, &post);
+        self.next_post_id += 1;
+
+        //push to user's post list
+        let mut user_posts = self.user_posts.get(&env::predecessor_account_id()).unwrap_or_default();
+        
+        user_posts.push(post_id);
+        self.user_posts.insert(&env::predecessor_account_id(), &user_posts); 
+        self.last_post_at.insert(&env::predecessor_account_id(), &env::block_timestamp());
+
+        //push to each tag's post list
+        for tag in tags {
+            let mut tag_posts = self.tag_posts.get(&tag).unwrap_or_default();
+            tag_posts.push(post_id);
+            self.tag_posts.insert(&tag, &tag_posts);
+        }
+
+        let title = post.get_title();
+
+        // Use env::log to record logs permanently to the blockchain!
+        env::log(format!("Post '{}' was created", title).as_bytes());
+        log_event("post_created", json!({ "post_id": post_id, "author": post.get_author() }));
+
+        post_id
+    }
+
+    // downvoted to the hide threshold
+    fn is_hidden(&self, post: &Post) -> bool {
+        post.get_points() <= self.hide_threshold
+    }
+
+    // binary search for the first post created at or after timestamp, ids grow with created_at
+    fn first_post_id_at(&self, timestamp: u64) -> PostId {
+        let (mut low, mut high) = (0, self.next_post_id);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            // a removed id counts as the next post that still exists
+            match (mid..high).find_map(|post_id| self.posts.get(&post_id)) {
+                Some(post) if post.get_created_at() < timestamp => low = post.get_post_id() + 1,
+                _ => high = mid,
+            }
+        }
+        low
+    }
+
+    fn require_post(&self, post_id: PostId) -> Post {
+        match self.posts.get(&post_id) {
+            Some(post) => post,
+            None => panic!("Post does not exist"),
+        }
+    }
+
+    // soft-deleted posts take no new comments, votes, reactions or donations
+    fn require_live_post(&self, post_id: PostId) -> Post {
+        let post = self.require_post(post_id);
+        assert!(!post.is_deleted(), "Post is deleted");
+        post
+    }
+
+    fn assert_no_donations(&self, post_id: PostId) {
+        if let Some(post) = self.posts.get(&post_id) {
+            assert_eq!(0, post.get_donation_count(), "Post has donations, soft delete it instead");
+        }
+    }
+
+    fn remove_post(&mut self, post_id: usize) {
+        let post = match self.posts.remove(&post_id) {
+            Some(post) => post,
+            None => panic!("Post does not exist"),
+        };
+
+        // drop the id from the author's post list as well
+        let author = post.get_author();
+        let mut user_posts = self.user_posts.get(&author).unwrap_or_default();
+        user_posts.retain(|&x| x != post_id);
+        if user_posts.is_empty() {
+            self.user_posts.remove(&author);
+        } else {
+            self.user_posts.insert(&author, &user_posts);
+        }
+
+        for tag in post.get_tags() {
+            let mut tag_posts = self.tag_posts.get(&tag).unwrap_or_default();
+            tag_posts.retain(|&x| x != post_id);
+            if tag_posts.is_empty() {
+                self.tag_posts.remove(&tag);
+            } else {
+                self.tag_posts.insert(&tag, &tag_posts);
+            }
+        }
+
+        self.slugs.remove(&post.get_slug());
+        self.scheduled_posts.remove(&post_id);
+        self.featured.retain(|&x| x != post_id);
+        self.reports.remove(&post_id);
+        if let Some(client_nonce) = self.post_client_nonces.remove(&post_id) {
+            self.post_nonces.remove(&(author.clone(), client_nonce));
+        }
+
+        for comment_id in post.get_comments() {
+            self.remove_comment_record(comment_id);
+        }
+
+        log_event("post_deleted", json!({ "post_id": post_id, "author": author }));
+    }
+
+    // slug from the title, suffixed with -2, -3, ... when it is already taken
+    fn assign_slug(&mut self, post: &mut Post) {
+        let mut base = slugify(&post.get_title());
+        if base.is_empty() {
+            base = format!("post-{}", post.get_post_id());
+        }
+
+        let mut slug = base.clone();
+        let mut suffix = 2;
+        while self.slugs.get(&slug).is_some() {
+            slug = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+
+        self.slugs.insert(&slug, &post.get_post_id());
+        post.set_slug(slug);
+    }
+
+    // token is the fungible token contract, None for NEAR, holder_contract is the NFT contract for holder donations
+    fn record_donation(&mut self, post_id: PostId, donor: AccountId, amount: Balance, message: String, token: Option<AccountId>, holder_contract: Option<AccountId>) {
+        // Check if the post exists
+        let mut post = self.require_live_post(post_id);
+
+        let mut donation_log = self.new_donation_log(post_id, &donor, amount, message, token.clone());
+        if let Some(nft_contract) = holder_contract {
+            donation_log.mark_holder(nft_contract);
+        }
+        let net_amount = donation_log.get_net_amount();
+        self.donation_posts.insert(&donation_log.get_donation_id(), &post_id);
+
+        // save to donation log
+        post.add_donation_logs(donation_log);
+        self.posts.insert(&post_id, &post);
+
+        // credit the author, the funds stay here until they withdraw them
+        let author = post.get_author();
+        match token.clone() {
+            None => {
+                let balance = self.pending_donations.get(&author).unwrap_or(0);
+                self.pending_donations.insert(&author, &(balance + net_amount));
+
+                let donated = self.donor_totals.get(&donor).unwrap_or(0);
+                self.donor_totals.insert(&donor, &(donated + amount));
+            },
+            Some(token) => {
+                let key = (author.clone(), token);
+                let balance = self.pending_ft_donations.get(&key).unwrap_or(0);
+                self.pending_ft_donations.insert(&key, &(balance + amount));
+            },
+        }
+
+        log_event("donation_received", json!({
+            "post_id": post_id,
+            "donor": donor,
+            "author": author,
+            "amount": amount.to_string(),
+            "token": token,
+        }));
+    }
+
+    // checks a post or comment donation and updates the shared counters, the caller stores the log and indexes it
+    fn new_donation_log(&mut self, post_id: PostId, donor: &AccountId, amount: Balance, message: String, token: Option<AccountId>) -> DonationLog {
+        self.assert_donation_allowed(post_id, donor, amount, &token);
+
+        let created_at = env::block_timestamp();
+        self.last_donation_at.insert(&(post_id, donor.clone()), &created_at);
+
+        let mut donation_log = DonationLog::new(self.next_donation_id, amount, donor.clone(), created_at, message, post_id, token);
+        self.deduct_donation_fee(&mut donation_log);
+
+        self.next_donation_id += 1;
+        self.total_donations += 1;
+        donation_log
+    }
+
+    // the minimum is in yoctoNEAR, it doesn't apply to tokens
+    fn assert_donation_allowed(&self, post_id: PostId, donor: &AccountId, amount: Balance, token: &Option<AccountId>) {
+        if token.is_none() {
+            assert!(amount > 0, "Attached deposit must be greater than 0");
+            assert!(amount >= self.min_donation, "Donation must be at least {} yoctoNEAR", self.min_donation);
+        } else {
+            assert!(amount > 0, "Amount must be greater than 0");
+        }
+
+        if let Some(remaining) = self.donation_cooldown_remaining(post_id, donor) {
+            panic!("Please wait {} seconds before donating to this post again", remaining);
+        }
+    }
+
+    // same checks as assert_donation_allowed, for callbacks that must not panic
+    fn is_donation_allowed(&self, post_id: PostId, donor: &AccountId, amount: Balance, token: &Option<AccountId>) -> bool {
+        let minimum = if token.is_none() { self.min_donation } else { 0 };
+
+        amount > 0 && amount >= minimum && self.donation_cooldown_remaining(post_id, donor).is_none()
+    }
+
+    // seconds until the donor may donate to the post again, None if they may now
+    fn donation_cooldown_remaining(&self, post_id: PostId, donor: &AccountId) -> Option<u64> {
+        let last_donation_at = self.last_donation_at.get(&(post_id, donor.clone()))?;
+        let next_donation_at = last_donation_at + self.donation_cooldown;
+        let now = env::block_timestamp();
+
+        if now < next_donation_at {
+            Some((next_donation_at - now).div_ceil(1_000_000_000))
+        } else {
+            None
+        }
+    }
+
+    // the platform fee only applies to NEAR
+    fn deduct_donation_fee(&mut self, donation_log: &mut DonationLog) {
+        if donation_log.is_near() {
+            let fee = donation_log.get_amount() * Balance::from(self.donation_fee_bps) / 10_000;
+            donation_log.deduct_fee(fee);
+            self.collected_fees += fee;
+        }
+    }
+
+    fn add_comment(&mut self, post_id: usize, body: String, parent_comment_id: Option<CommentId>) -> CommentId {
+        self.assert_not_paused();
+        // Check if the post exists
+        let mut post = self.require_live_post(post_id);
+        assert!(post.are_comments_enabled(), "Comments are disabled for this post");
+        assert!(post.get_comments().len() < self.max_comments_per_post, "Comment limit reached");
+        assert!(!self.is_blocked(post.get_author(), env::predecessor_account_id()), "You are blocked from commenting on this author's posts");
+        self.assert_valid_comment(&body);
+
+        // replies must point at a comment of the same post, nested at most MAX_REPLY_DEPTH deep
+        if let Some(parent_comment_id) = parent_comment_id {
+            assert!(post.get_comments().contains(&parent_comment_id), "Parent comment does not exist");
+            assert!(self.comment_depth(parent_comment_id) < MAX_REPLY_DEPTH, "Replies can be nested at most {} levels deep", MAX_REPLY_DEPTH);
+        }
+
+        let initial_storage_usage = env::storage_usage();
+        let author = env::predecessor_account_id();
+        let created_at = env::block_timestamp();
+
+        let comment = Comment::new(self.next_comment_id, body, author.clone(), created_at, parent_comment_id);
+
+        // write the post back, get() only returns a copy
+        post.add_comment(comment.get_comment_id());
+        self.posts.insert(&post_id, &post);
+
+        self.comments.insert(&comment.get_comment_id(), &comment);
+        self.comment_posts.insert(&comment.get_comment_id(), &post_id);
+        self.next_comment_id += 1;
+
+        let mut author_comments = self.author_comments.get(&author).unwrap_or_default();
+        author_comments.push(comment.get_comment_id());
+        self.author_comments.insert(&author, &author_comments);
+
+        for account_id in extract_mentions(&comment.get_body()) {
+            if account_id == author {
+                continue;
+            }
+            let mut notifications = self.notifications.get(&account_id).unwrap_or_default();
+            if notifications.len() == MAX_NOTIFICATIONS {
+                notifications.remove(0);
+            }
+            notifications.push(Notification::new(post_id, comment.get_comment_id(), author.clone(), created_at));
+            self.notifications.insert(&account_id, &notifications);
+        }
+
+        self.charge_storage(initial_storage_usage);
+
+        log_event("comment_added", json!({
+            "post_id": post_id,
+            "comment_id": comment.get_comment_id(),
+            "parent_comment_id": parent_comment_id,
+            "author": comment.get_author(),
+        }));
+
+        comment.get_comment_id()
+    }
+
+    // drops the comment along with its post and author index entries and its tips
+    fn remove_comment_record(&mut self, comment_id: CommentId) {
+        self.comment_posts.remove(&comment_id);
+        for donation_log in self.comment_donations.remove(&comment_id).unwrap_or_default() {
+            self.donation_comments.remove(&donation_log.get_donation_id());
+        }
+        let comment = match self.comments.remove(&comment_id) {
+            Some(comment) => comment,
+            None => return,
+        };
+
+        let author = comment.get_author();
+        let mut author_comments = self.author_comments.get(&author).unwrap_or_default();
+        author_comments.retain(|&x| x != comment_id);
+        if author_comments.is_empty() {
+            self.author_comments.remove(&author);
+        } else {
+            self.author_comments.insert(&author, &author_comments);
+        }
+    }
+
+    // the caller pays for the bytes written since initial_storage_usage, the rest of the deposit is refunded
+    fn charge_storage(&mut self, initial_storage_usage: u64) {
+        let account_id = env::predecessor_account_id();
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        let storage_cost = Balance::from(storage_used) * env::storage_byte_cost();
+
+        let deposit = env::attached_deposit();
+        assert!(deposit >= storage_cost, "Attached deposit must cover the storage cost of {} yoctoNEAR", storage_cost);
+
+        let paid = self.storage_paid.get(&account_id).unwrap_or(0);
+        self.storage_paid.insert(&account_id, &(paid + storage_cost));
+
+        let refund = deposit - storage_cost;
+        if refund > 0 {
+            Promise::new(account_id).transfer(refund);
+        }
+    }
+
+    // top level comments are at depth 0
+    fn comment_depth(&self, comment_id: CommentId) -> usize {
+        let mut depth = 0;
+        let mut parent_comment_id = self.comments.get(&comment_id).and_then(|comment| comment.get_parent_comment_id());
+
+        while let Some(comment_id) = parent_comment_id {
+            depth += 1;
+            parent_comment_id = self.comments.get(&comment_id).and_then(|comment| comment.get_parent_comment_id());
+        }
+
+        depth
+    }
+
+    fn assert_allowed_author(&self) {
+        if self.allowlist_enabled {
+            assert!(self.allowed_authors.contains(&env::predecessor_account_id()), "Only allowed authors can create posts");
+        }
+    }
+
+    fn assert_post_cooldown(&self) {
+        if let Some(last_post_at) = self.last_post_at.get(&env::predecessor_account_id()) {
+            let next_post_at = last_post_at + self.post_cooldown;
+            let now = env::block_timestamp();
+            if now < next_post_at {
+                // round up so a wait of half a second isn't reported as 0
+                let remaining = (next_post_at - now).div_ceil(1_000_000_000);
+                panic!("Please wait {} seconds before creating another post", remaining);
+            }
+        }
+    }
+
+    fn assert_valid_comment(&self, body: &str) {
+        let length = body.chars().count();
+        assert!(length >= self.min_comment_len, "Comment must be at least {} characters long", self.min_comment_len);
+        assert!(length <= self.max_comment_len, "Comment must be at most {} characters long", self.max_comment_len);
+        self.assert_allowed_content(body);
+    }
+
+    // the message doesn't say which word matched, so it can't be used to probe the list
+    fn assert_allowed_content(&self, text: &str) {
+        if self.banned_words.is_empty() {
+            return;
+        }
+
+        let text = text.to_lowercase();
+        assert!(!self.banned_words.iter().any(|word| text.contains(word.as_str())), "Content rejected");
+    }
+
+    fn assert_not_paused(&self) {
+        assert!(!self.paused, "Contract is paused");
+    }
+
+    fn can_moderate(&self, account_id: &AccountId) -> bool {
+        *account_id == self.owner || self.moderators.contains(account_id)
+    }
+}
+
+fn assert_valid_post(title: &str, body: &str) {
+    let title_length = title.chars().count();
+    assert!(title_length > 0, "Title must not be empty");
+    assert!(title_length <= MAX_TITLE_LENGTH, "Title must be at most {} characters long", MAX_TITLE_LENGTH);
+
+    let body_length = body.chars().count();
+    assert!(body_length > 0, "Body must not be empty");
+    assert!(body_length <= MAX_BODY_LENGTH, "Body must be at most {} characters long", MAX_BODY_LENGTH);
+}
+
+fn assert_valid_post_extras(cover_image_url: &str, excerpt: &str) {
+    assert!(excerpt.chars().count() <= MAX_EXCERPT_LENGTH, "Excerpt must be at most {} characters long", MAX_EXCERPT_LENGTH);
+    assert!(cover_image_url.len() <= MAX_URL_LENGTH, "Cover image url must be at most {} bytes long", MAX_URL_LENGTH);
+}
+
+fn trending_score(post: &Post, now: u64) -> f64 {
+    let age_hours = now.saturating_sub(post.get_created_at()) as f64 / 3_600_000_000_000.0;
+    let donated_near = post.get_total_donation() as f64 / 1e24;
+    let engagement = post.get_points() as f64 + 2.0 * post.get_comments().len() as f64 + donated_near;
+
+    engagement / (age_hours + 2.0).powf(1.5)
+}
+
+// lowercase ascii letters and digits, words joined by single hyphens
+fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+
+    for c in title.to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if (c.is_whitespace() || c == '-') && !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.trim_end_matches('-').to_string()
+}
+
+// accounts mentioned as @account.near, invalid account ids are ignored
+fn extract_mentions(body: &str) -> Vec<AccountId> {
+    let mut mentions: Vec<AccountId> = Vec::new();
+
+    for word in body.split_whitespace() {
+        let account_id = match word.strip_prefix('@') {
+            Some(account_id) => account_id.trim_end_matches(|c: char| !c.is_ascii_alphanumeric()),
+            None => continue,
+        };
+        if env::is_valid_account_id(account_id.as_bytes()) && !mentions.iter().any(|x| x == account_id) {
+            mentions.push(account_id.to_string());
+        }
+        if mentions.len() == MAX_MENTIONS {
+            break;
+        }
+    }
+
+    mentions
+}
+
+// lowercase, drop blanks and duplicates, keep the original order
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+
+    assert!(normalized.len() <= MAX_TAGS, "Post can have at most {} tags", MAX_TAGS);
+    normalized
+}
+
+/*
+ * The rest of this file holds the inline tests for the code above
+ * Learn more about Rust tests: https://doc.rust-lang.org/book/ch11-01-writing-tests.html
+ *
+ * To run from contract directory:
+ * cargo test -- --nocapture
+ *
+ * From project root, to run in combination with frontend tests:
+ * yarn test
  *
  */
 #[cfg(test)]
 mod tests {
     use super::*;
     use near_sdk::{MockedBlockchain};
-    use near_sdk::{testing_env, VMContext};
+    use near_sdk::serde_json::Value;
+    use near_sdk::test_utils::get_logs;
+    use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig, VMContext};
+
+    // mock the context for testing, notice "signer_account_id" that was accessed above from env::
+    fn get_context(input: Vec<u8>, is_view: bool) -> VMContext {
+        VMContext {
+            current_account_id: "decentrablog.npmrunstart.testnet".to_string(),
+            signer_account_id: "alice_near".to_string(),
+            signer_account_pk: vec![0, 1, 2],
+            predecessor_account_id: "alice_near".to_string(),
+            input,
+            block_index: 0,
+            block_timestamp: 0,
+            account_balance: 100000000000000000000000000,
+            account_locked_balance: 0,
+            storage_usage: 0,
+            // enough to pay for the storage of a post or comment
+            attached_deposit: 100000000000000000000000,
+            prepaid_gas: 10u64.pow(18),
+            random_seed: vec![0, 1, 2],
+            is_view,
+            output_data_receivers: vec![],
+            epoch_height: 19,
+        }
+    }
+
+    // switch the caller, keeping the storage written by the previous calls
+    fn set_caller(context: &mut VMContext, account_id: &str) {
+        context.signer_account_id = account_id.to_string();
+        context.predecessor_account_id = account_id.to_string();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context.clone());
+    }
+
+    #[test]
+    fn create_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+        let post_id = contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(0, post_id);
+
+        //log id
+        env::log(format!("Debug here {}", contract.get_post(0).unwrap().get_post_id()).as_bytes());
+        
+        assert_eq!(
+            "This is the title".to_string(),
+            contract.get_post(0).unwrap().get_title()
+        );
+        assert_eq!(
+            "Lets go Brandon!".to_string(),
+            contract.get_post(0).unwrap().get_body()
+        );
+        assert_eq!(1, contract.get_total_posts());
+        assert_eq!(0, contract.get_post(0).unwrap().get_post_id());
+
+        //test get_user_posts
+        let user_posts = contract.get_user_posts("alice_near".to_string());
+        assert_eq!(1, user_posts.len());
+        assert_eq!(0, user_posts[0].get_post_id());
+
+        // ids keep incrementing
+        let post_id = contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(1, post_id);
+    }
+
+    #[test]
+    fn create_post_with_max_title_length() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        // multibyte characters count once
+        let title = "é".repeat(MAX_TITLE_LENGTH);
+        contract.create_post(title.clone(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(title, contract.get_post(0).unwrap().get_title());
+    }
+
+    #[test]
+    #[should_panic(expected = "Title must be at most 200 characters long")]
+    fn create_post_with_too_long_title() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("a".repeat(MAX_TITLE_LENGTH + 1), "Lets go Brandon!".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Title must not be empty")]
+    fn create_post_with_empty_title() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Body must be at most 20000 characters long")]
+    fn create_post_with_too_long_body() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "a".repeat(MAX_BODY_LENGTH + 1), None, None, None, None);
+    }
+
+    #[test]
+    fn posts_by_tag() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        let tags = vec!["Rust".to_string(), "near ".to_string(), "rust".to_string(), "".to_string()];
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), Some(tags), None, None, None);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), Some(vec!["near".to_string()]), None, None, None);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        // normalized and deduped
+        assert_eq!(vec!["rust".to_string(), "near".to_string()], contract.get_post(0).unwrap().get_tags());
+
+        let posts = contract.get_posts_by_tag("RUST".to_string(), 0, 10);
+        assert_eq!(1, posts.len());
+        assert_eq!(0, posts[0].get_post_id());
+
+        let posts = contract.get_posts_by_tag("near".to_string(), 0, 10);
+        assert_eq!(2, posts.len());
+        assert_eq!(1, posts[1].get_post_id());
+
+        // deleted posts leave the tag index
+        contract.delete_post(0, None);
+        assert_eq!(0, contract.get_posts_by_tag("rust".to_string(), 0, 10).len());
+        assert_eq!(0, contract.get_posts_by_tag("unknown".to_string(), 0, 10).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "Post can have at most 5 tags")]
+    fn create_post_with_too_many_tags() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        let tags = (0..6).map(|i| format!("tag{}", i)).collect();
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), Some(tags), None, None, None);
+    }
+
+    // parse the last EVENT_JSON log
+    fn last_event() -> Value {
+        let log = get_logs().into_iter().rfind(|log| log.starts_with("EVENT_JSON:")).unwrap();
+        near_sdk::serde_json::from_str(&log["EVENT_JSON:".len()..]).unwrap()
+    }
+
+    #[test]
+    fn events_are_logged() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        let event = last_event();
+        assert_eq!("decentrablog", event["standard"]);
+        assert_eq!("1.0.0", event["version"]);
+        assert_eq!("post_created", event["event"]);
+        assert_eq!(0, event["data"][0]["post_id"]);
+        assert_eq!("alice_near", event["data"][0]["author"]);
+
+        contract.create_comment(0, "This is the comment".to_string());
+        let event = last_event();
+        assert_eq!("comment_added", event["event"]);
+        assert_eq!(0, event["data"][0]["comment_id"]);
+
+        context.attached_deposit = 1000000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Support Trump for the USA".to_string());
+        let event = last_event();
+        assert_eq!("donation_received", event["event"]);
+        assert_eq!("bob_near", event["data"][0]["donor"]);
+        assert_eq!("1000000", event["data"][0]["amount"]);
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        contract.delete_post(0, Some(true));
+        let event = last_event();
+        assert_eq!("post_deleted", event["event"]);
+        assert_eq!(0, event["data"][0]["post_id"]);
+    }
+
+    #[test]
+    fn draft_is_hidden_from_feed() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_draft("This is the draft".to_string(), "Lets go Brandon!".to_string(), Some(vec!["near".to_string()]), None, None, None);
+
+        assert!(!contract.get_post(1).unwrap().is_published());
+        assert_eq!(1, contract.get_all_posts(0, 10, None, None).len());
+        assert_eq!(1, contract.get_paging_posts(1, 10).len());
+        assert_eq!(1, contract.get_posts().len());
+        assert_eq!(0, contract.get_posts_by_tag("near".to_string(), 0, 10).len());
+
+        // the author still sees it
+        let posts = contract.get_posts_paginated("alice_near".to_string(), 0, 10);
+        assert_eq!(2, posts.len());
+        assert_eq!(1, posts[1].get_post_id());
+
+        contract.publish_post(1);
+        assert_eq!(2, contract.get_all_posts(0, 10, None, None).len());
+        assert_eq!(1, contract.get_posts_by_tag("near".to_string(), 0, 10).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only author can publish the post")]
+    fn non_author_cannot_publish() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_draft("This is the draft".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.publish_post(0);
+    }
+
+    #[test]
+    fn posts_by_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        set_caller(&mut context, "bob_near");
+        for i in 0..3 {
+            contract.create_post(format!("This is the title {}", i), "Lets go Brandon!".to_string(), None, None, None, None);
+        }
+
+        // same posts as bob sees for himself
+        let own_ids: Vec<usize> = contract.get_posts_paginated("bob_near".to_string(), 0, 10).iter().map(|post| post.get_post_id()).collect();
+
+        set_caller(&mut context, "alice_near");
+        let author_ids: Vec<usize> = contract.get_posts_by_author("bob_near".to_string(), 0, 10).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(own_ids, author_ids);
+
+        assert_eq!(1, contract.get_posts_by_author("bob_near".to_string(), 2, 10).len());
+        assert_eq!(0, contract.get_posts_by_author("carol_near".to_string(), 0, 10).len());
+    }
+
+    #[test]
+    fn storage_is_paid_by_the_caller() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        let paid_for_post = contract.storage_balance_of("alice_near".to_string()).0;
+        assert!(paid_for_post > 0);
+
+        contract.create_comment(0, "This is the comment".to_string());
+        assert!(contract.storage_balance_of("alice_near".to_string()).0 > paid_for_post);
+        assert_eq!(0, contract.storage_balance_of("bob_near".to_string()).0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must cover the storage cost")]
+    fn create_post_with_insufficient_deposit() {
+        let mut context = get_context(vec![], false);
+        context.attached_deposit = 1;
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    fn follow_and_unfollow() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.follow("bob_near".to_string());
+        contract.follow("carol_near".to_string());
+        assert_eq!(vec!["bob_near".to_string(), "carol_near".to_string()], contract.get_following("alice_near".to_string()));
+
+        contract.unfollow("bob_near".to_string());
+        assert_eq!(vec!["carol_near".to_string()], contract.get_following("alice_near".to_string()));
+        assert!(contract.get_following("bob_near".to_string()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Already following this account")]
+    fn duplicate_follow() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.follow("bob_near".to_string());
+        contract.follow("bob_near".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot follow yourself")]
+    fn follow_yourself() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.follow("alice_near".to_string());
+    }
+
+    #[test]
+    fn following_feed() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        for author in ["bob_near", "carol_near", "dave_near", "bob_near"].iter() {
+            set_caller(&mut context, author);
+            contract.create_post(format!("Post by {}", author), "Lets go Brandon!".to_string(), None, None, None, None);
+        }
+
+        set_caller(&mut context, "alice_near");
+        contract.follow("bob_near".to_string());
+        contract.follow("carol_near".to_string());
+
+        let post_ids: Vec<usize> = contract.get_following_feed("alice_near".to_string(), 0, 10).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![3, 1, 0], post_ids);
+
+        assert_eq!(1, contract.get_following_feed("alice_near".to_string(), 2, 10).len());
+        assert!(contract.get_following_feed("dave_near".to_string(), 0, 10).is_empty());
+    }
+
+    #[test]
+    fn add_and_remove_bookmarks() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        contract.bookmark(1);
+        contract.bookmark(0);
+        let post_ids: Vec<usize> = contract.get_bookmarks("alice_near".to_string()).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![1, 0], post_ids);
+
+        contract.remove_bookmark(1);
+        let post_ids: Vec<usize> = contract.get_bookmarks("alice_near".to_string()).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![0], post_ids);
+
+        assert!(contract.get_bookmarks("bob_near".to_string()).is_empty());
+    }
+
+    #[test]
+    fn bookmark_of_deleted_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.bookmark(0);
+        contract.delete_post(0, None);
+
+        assert!(contract.get_bookmarks("alice_near".to_string()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Post does not exist")]
+    fn bookmark_missing_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.bookmark(0);
+    }
+
+    #[test]
+    fn record_view_counts_each_account_once() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(0, contract.get_post(0).unwrap().get_views());
+
+        assert_eq!(1, contract.record_view(0));
+        assert_eq!(1, contract.record_view(0));
+
+        set_caller(&mut context, "bob_near");
+        assert_eq!(2, contract.record_view(0));
+        assert_eq!(2, contract.get_post(0).unwrap().get_views());
+    }
+
+    #[test]
+    fn post_slugs() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("Hello, World!  It's  NEAR - time".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("Hello world".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("hello WORLD?".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("!!! ???".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        assert_eq!("hello-world-its-near-time".to_string(), contract.get_post(0).unwrap().get_slug());
+        assert_eq!("hello-world".to_string(), contract.get_post(1).unwrap().get_slug());
+        assert_eq!("hello-world-2".to_string(), contract.get_post(2).unwrap().get_slug());
+        assert_eq!("post-3".to_string(), contract.get_post(3).unwrap().get_slug());
+
+        assert_eq!(2, contract.get_post_by_slug("hello-world-2".to_string()).unwrap().get_post_id());
+        assert!(contract.get_post_by_slug("unknown".to_string()).is_none());
+
+        // deleting a post frees its slug
+        contract.delete_post(1, None);
+        assert!(contract.get_post_by_slug("hello-world".to_string()).is_none());
+    }
+
+    #[test]
+    fn user_without_posts() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let contract = Blog::default();
+
+        assert_eq!(0, contract.get_user_posts("alice_near".to_string()).len());
+    }
+
+    #[test]
+    fn user_with_three_posts() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        for i in 0..3 {
+            contract.create_post(format!("This is the title {}", i), "Lets go Brandon!".to_string(), None, None, None, None);
+        }
+
+        let user_posts = contract.get_user_posts("alice_near".to_string());
+        assert_eq!(3, user_posts.len());
+        assert_eq!(0, user_posts[0].get_post_id());
+        assert_eq!(2, user_posts[2].get_post_id());
+
+        // other users still have nothing
+        assert_eq!(0, contract.get_user_posts("bob_near".to_string()).len());
+    }
+
+    #[test]
+    fn delete_a_post_then_add_two_posts() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.delete_post(0, None);
+        
+        assert_eq!(0, contract.get_total_posts(), "Total posts should be 0");
+
+        // add a post
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(2, contract.get_total_posts());
+
+        //next post id
+        assert_eq!(3, contract.get_next_post_id());
+    }
+
+    #[test]
+    fn delete_post_clears_user_posts() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.delete_post(0, None);
+
+        let user_posts = contract.get_user_posts("alice_near".to_string());
+        assert_eq!(1, user_posts.len());
+        assert_eq!(1, user_posts[0].get_post_id());
+
+        let posts = contract.get_posts_paginated("alice_near".to_string(), 0, 10);
+        assert_eq!(1, posts.len());
+        assert_eq!(1, posts[0].get_post_id());
+    }
+
+    #[test]
+    fn get_post_returns_none_for_deleted_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert!(contract.get_post(0).is_some());
+
+        contract.delete_post(0, None);
+        assert!(contract.get_post(0).is_none());
+        assert!(contract.get_post(1).is_none());
+    }
+
+    #[test]
+    fn author_deletes_own_post() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.delete_my_post(0);
+
+        assert!(contract.get_post(0).is_none());
+        assert_eq!(1, contract.get_total_posts());
+        assert_eq!(1, contract.get_user_posts("bob_near".to_string()).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only author can delete the post")]
+    fn non_author_cannot_delete_own_post() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "carol_near");
+        contract.delete_my_post(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Post does not exist")]
+    fn delete_missing_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.delete_post(0, None);
+    }
+
+    #[test]
+    fn author_can_edit_post() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.block_timestamp = 100;
+        context.storage_usage = env::storage_usage();
+        testing_env!(context);
+        contract.edit_post(0, "This is the new title".to_string(), "Lets go Brandon again!".to_string());
+
+        let post = contract.get_post(0).unwrap();
+        assert_eq!("This is the new title".to_string(), post.get_title());
+        assert_eq!("Lets go Brandon again!".to_string(), post.get_body());
+        assert_eq!(100, post.get_updated_at());
+    }
+
+    #[test]
+    fn post_history() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("Original title".to_string(), "Original body".to_string(), None, None, None, None);
+        assert!(contract.get_post_history(0).is_empty());
+
+        context.block_timestamp = 100;
+        set_caller(&mut context, "alice_near");
+        contract.edit_post(0, "Second title".to_string(), "Second body".to_string());
+        context.block_timestamp = 200;
+        set_caller(&mut context, "alice_near");
+        contract.edit_post(0, "Third title".to_string(), "Third body".to_string());
+
+        let history = contract.get_post_history(0);
+        assert_eq!(2, history.len());
+        assert_eq!("Original title", history[0].get_title());
+        assert_eq!("Original body", history[0].get_body());
+        assert_eq!(100, history[0].get_edited_at());
+        assert_eq!("Second title", history[1].get_title());
+        assert_eq!(200, history[1].get_edited_at());
+        assert_eq!("Third title", contract.get_post(0).unwrap().get_title());
+
+        // only the last 10 revisions are kept
+        for i in 0..10 {
+            contract.edit_post(0, format!("Title {}", i), "Lets go Brandon!".to_string());
+        }
+        let history = contract.get_post_history(0);
+        assert_eq!(10, history.len());
+        assert_eq!("Third title", history[0].get_title());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only author can edit the post")]
+    fn non_author_cannot_edit_post() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.edit_post(0, "This is the new title".to_string(), "Lets go Brandon again!".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Title must not be empty")]
+    fn edit_post_with_empty_title() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.edit_post(0, "".to_string(), "Lets go Brandon again!".to_string());
+    }
+
+    #[test]
+    fn blog_stats() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(2, "This is the comment".to_string());
+        contract.create_comment(2, "This is the comment".to_string());
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "carol_near");
+        contract.donate(0, "Donation".to_string());
+
+        let stats = contract.get_stats();
+        assert_eq!(3, stats.total_posts);
+        assert_eq!(3, stats.total_comments);
+        assert_eq!(1, stats.total_donations);
+        assert_eq!(3, stats.next_post_id);
+        assert_eq!(2, stats.total_authors);
+
+        // deleting bob's only post drops him from the authors
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        contract.delete_post(2, None);
+        assert_eq!(1, contract.get_stats().total_authors);
+    }
+
+    #[test]
+    fn migrate_old_state() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+
+        let mut old_state = migration::OldBlog {
+            owner: "alice_near".to_string(),
+            user_posts: UnorderedMap::new(b"user_posts".to_vec()),
+            posts: UnorderedMap::new(b"posts".to_vec()),
+            comments: UnorderedMap::new(b"comments".to_vec()),
+            next_post_id: 1,
+            next_comment_id: 1,
+            next_donation_id: 0,
+        };
+
+        let mut upvotes = std::collections::HashSet::new();
+        upvotes.insert("bob_near".to_string());
+        let donation_log = migration::OldDonationLog {
+            donation_id: 0,
+            amount: 1000,
+            donor: "bob_near".to_string(),
+            created_at: 0,
+            message: "Donation".to_string(),
+            post_id: 0,
+        };
+
+        old_state.user_posts.insert(&"alice_near".to_string(), &vec![0]);
+        old_state.posts.insert(&0, &migration::OldPost {
+            post_id: 0,
+            title: "This is the title".to_string(),
+            body: "Lets go Brandon!".to_string(),
+            author: "alice_near".to_string(),
+            created_at: 10,
+            comments: vec![0],
+            upvotes,
+            downvotes: std::collections::HashSet::new(),
+            donation_logs: vec![donation_log],
+        });
+        old_state.comments.insert(&0, &migration::OldComment {
+            comment_id: 0,
+            body: "This is the comment".to_string(),
+            author: "bob_near".to_string(),
+            created_at: 20,
+        });
+        env::state_write(&old_state);
+
+        // migrate is private, it has to be called by the contract itself
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context);
+        let contract = Blog::migrate();
+
+        assert_eq!("alice_near".to_string(), contract.get_owner());
+        assert_eq!(2, contract.get_version());
+        assert_eq!(1, contract.get_next_post_id());
+
+        let post = contract.get_post(0).unwrap();
+        assert_eq!("This is the title".to_string(), post.get_title());
+        assert_eq!(10, post.get_updated_at());
+        assert!(post.get_tags().is_empty());
+        assert!(post.is_published());
+        assert_eq!("this-is-the-title".to_string(), post.get_slug());
+        assert_eq!(vec![0], post.get_comments());
+        assert_eq!((1, 0), contract.get_votes_statistics(0));
+        assert_eq!(1000, post.get_total_donation());
+
+        let comment = contract.get_comment(0, 0).unwrap();
+        assert_eq!("This is the comment".to_string(), comment.get_body());
+        assert_eq!(None, comment.get_parent_comment_id());
+        assert!(!comment.is_edited());
+
+        assert_eq!(1, contract.get_user_posts("alice_near".to_string()).len());
+        assert_eq!(1, contract.get_total_donations());
+        assert_eq!(1, contract.get_next_donation_id());
+        assert_eq!(0, contract.get_pending_donations("alice_near".to_string()).0);
+        assert!(contract.get_moderators().is_empty());
+    }
+
+    #[test]
+    fn migrate_old_state_cleans_up_indexes() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+
+        let mut old_state = migration::OldBlog {
+            owner: "alice_near".to_string(),
+            user_posts: UnorderedMap::new(b"user_posts".to_vec()),
+            posts: UnorderedMap::new(b"posts".to_vec()),
+            comments: UnorderedMap::new(b"comments".to_vec()),
+            next_post_id: 2,
+            next_comment_id: 0,
+            next_donation_id: 0,
+        };
+
+        // the old contract logged every donation with id 0
+        // post 2 was deleted, but the old delete_post left it in user_posts, bob's only post was too
+        old_state.user_posts.insert(&"alice_near".to_string(), &vec![0, 1, 2]);
+        old_state.user_posts.insert(&"bob_near".to_string(), &vec![3]);
+        for post_id in 0..2 {
+            let donation_logs = (0..2).map(|i| migration::OldDonationLog {
+                donation_id: 0,
+                amount: 1000,
+                donor: "bob_near".to_string(),
+                created_at: 0,
+                message: format!("Donation {} to post {}", i, post_id),
+                post_id,
+            }).collect();
+            old_state.posts.insert(&post_id, &migration::OldPost {
+                post_id,
+                title: format!("This is title {}", post_id),
+                body: "Lets go Brandon!".to_string(),
+                author: "alice_near".to_string(),
+                created_at: 10,
+                comments: vec![],
+                upvotes: std::collections::HashSet::new(),
+                downvotes: std::collections::HashSet::new(),
+                donation_logs,
+            });
+        }
+        env::state_write(&old_state);
+
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context);
+        let mut contract = Blog::migrate();
+
+        assert_eq!(vec![0, 1], contract.user_posts.get(&"alice_near".to_string()).unwrap());
+        assert_eq!(1, contract.get_stats().total_authors);
+
+        assert_eq!(4, contract.get_next_donation_id());
+        assert_eq!("Donation 0 to post 0", contract.get_donation(0).unwrap().get_message());
+        assert_eq!("Donation 1 to post 0", contract.get_donation(1).unwrap().get_message());
+        assert_eq!("Donation 0 to post 1", contract.get_donation(2).unwrap().get_message());
+        assert_eq!("Donation 1 to post 1", contract.get_donation(3).unwrap().get_message());
+
+        // the second log of a post can be redacted on its own
+        let mut context = get_context(vec![], false);
+        context.storage_usage = env::storage_usage();
+        testing_env!(context);
+        contract.redact_donation_message(0, 1);
+        assert_eq!("Donation 0 to post 0", contract.get_donation(0).unwrap().get_message());
+        assert_eq!("", contract.get_donation(1).unwrap().get_message());
+    }
+
+    #[test]
+    fn return_owner_account_id() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let contract = Blog::default();
+        assert_eq!(
+            "alice_near".to_string(),
+            contract.get_owner()
+        );
+    }
+
+    #[test]
+    fn transfer_ownership() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.transfer_ownership("bob_near".to_string());
+        assert_eq!("bob_near".to_string(), contract.get_owner());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can transfer ownership")]
+    fn non_owner_cannot_transfer_ownership() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        set_caller(&mut context, "bob_near");
+        contract.transfer_ownership("bob_near".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid account id")]
+    fn transfer_ownership_to_invalid_account() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.transfer_ownership("Not An Account!".to_string());
+    }
+
+    #[test]
+    fn new_sets_owner() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let contract = Blog::new("bob_near".to_string());
+
+        assert_eq!("bob_near".to_string(), contract.get_owner());
+        assert_eq!(0, contract.get_total_posts());
+        assert_eq!(STATE_VERSION, contract.get_version());
+    }
+
+    #[test]
+    #[should_panic(expected = "The contract has already been initialized")]
+    fn new_rejects_reinitialization() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let contract = Blog::new("bob_near".to_string());
+        env::state_write(&contract);
+
+        Blog::new("carol_near".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid account id")]
+    fn add_invalid_moderator() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.add_moderator("Invalid..ID".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid account id")]
+    fn follow_invalid_account() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.follow("Invalid..ID".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid account id")]
+    fn block_invalid_commenter() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.block_commenter("Invalid..ID".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn paused_contract_rejects_writes() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.set_paused(true);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    fn paused_contract_serves_views() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.set_paused(true);
+
+        assert!(contract.is_paused());
+        assert_eq!("This is the title".to_string(), contract.get_post(0).unwrap().get_title());
+
+        contract.set_paused(false);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(2, contract.get_total_posts());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can pause the contract")]
+    fn non_owner_cannot_pause() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        set_caller(&mut context, "bob_near");
+        contract.set_paused(true);
+    }
+
+    #[test]
+    fn owner_adds_moderator() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.add_moderator("bob_near".to_string());
+        assert!(contract.is_moderator("bob_near".to_string()));
+        assert_eq!(vec!["bob_near".to_string()], contract.get_moderators());
+
+        contract.remove_moderator("bob_near".to_string());
+        assert!(!contract.is_moderator("bob_near".to_string()));
+    }
+
+    #[test]
+    fn moderator_can_delete() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.add_moderator("bob_near".to_string());
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        set_caller(&mut context, "bob_near");
+        contract.delete_comment(0, 0);
+        contract.delete_post(0, None);
+
+        assert_eq!(0, contract.get_total_posts());
+        assert_eq!(0, contract.get_total_comments());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner or moderators can delete posts")]
+    fn random_account_cannot_delete() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "carol_near");
+        contract.delete_post(0, None);
+    }
+
+    #[test]
+    fn report_and_clear_post() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.report_post(0, "Spam".to_string());
+        contract.report_post(1, "Off topic".to_string());
+        set_caller(&mut context, "carol_near");
+        contract.report_post(0, "Spam".to_string());
+
+        set_caller(&mut context, "alice_near");
+        let reports = contract.get_reports(0, 10);
+        assert_eq!(3, reports.len());
+        assert_eq!(1, reports.iter().filter(|report| report.get_post_id() == 1).count());
+
+        contract.clear_reports(0);
+        let reports = contract.get_reports(0, 10);
+        assert_eq!(1, reports.len());
+        assert_eq!("Off topic".to_string(), reports[0].get_reason());
+    }
+
+    #[test]
+    #[should_panic(expected = "Post was already reported by this account")]
+    fn duplicate_report() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.report_post(0, "Spam".to_string());
+        contract.report_post(0, "Still spam".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner or moderators can view reports")]
+    fn reports_are_moderator_only() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.report_post(0, "Spam".to_string());
+        contract.get_reports(0, 10);
+    }
+
+    #[test]
+    fn create_comment() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        // Create the first post
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        assert_eq!(
+            "This is the comment".to_string(),
+            contract.get_comment(0, 0).unwrap().get_body()
+        );
+        assert_eq!(0, contract.get_comment(0, 0).unwrap().get_comment_id());
+
+        contract.create_comment(0, "This is comment 2, id 1".to_string());
+        contract.create_comment(0, "This is comment 3, id 2".to_string());
+
+        // Check if the comments is there
+        assert_eq!(
+            "This is comment 2, id 1".to_string(),
+            contract.get_comment(0, 1).unwrap().get_body()
+        );
+        assert_eq!(
+            "This is comment 3, id 2".to_string(),
+            contract.get_comment(0, 2).unwrap().get_body()
+        );
+
+        let comments = contract.get_post(0).unwrap().get_comments();
+
+        //assert size of comments
+        assert_eq!(3, comments.len(), "Comments size is not 3");
+        assert_eq!(3, contract.get_post_total_comments(0), "get_post_total_comments is not 3");
+
+        //Check comment string
+        assert_eq!(
+            "This is comment 2, id 1".to_string(),
+            contract.get_comment(0, 1).unwrap().get_body()
+        );
+        assert_eq!(
+            "This is comment 3, id 2".to_string(),
+            contract.get_comment(0, 2).unwrap().get_body()
+        );
+    }
+
+    #[test]
+    fn comment_is_persisted_on_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        // read the post back and check the comment is there
+        let post = contract.get_post(0).unwrap();
+        assert_eq!(vec![0], post.get_comments());
+
+        let comment = contract.get_comment(0, 0).unwrap();
+        assert_eq!("This is the comment".to_string(), comment.get_body());
+        assert_eq!("alice_near".to_string(), comment.get_author());
+
+        // counters only move once
+        assert_eq!(1, contract.get_next_comment_id());
+        assert_eq!(1, contract.get_total_comments());
+    }
+
+    #[test]
+    fn reply_to_comment() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+        contract.create_comment(0, "This is another comment".to_string());
+        contract.reply(0, 0, "This is the reply".to_string());
+
+        let replies = contract.get_replies(0, 0);
+        assert_eq!(1, replies.len());
+        assert_eq!("This is the reply".to_string(), replies[0].get_body());
+        assert_eq!(Some(0), replies[0].get_parent_comment_id());
+        assert_eq!(0, contract.get_replies(0, 1).len());
+        assert_eq!(3, contract.get_post_total_comments(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Parent comment does not exist")]
+    fn reply_to_missing_comment() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.reply(0, 0, "This is the reply".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Replies can be nested at most 3 levels deep")]
+    fn reply_too_deep() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+        for i in 0..4 {
+            contract.reply(0, i, "This is the reply".to_string());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Comment limit reached")]
+    fn comment_limit_per_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        assert_eq!(500, contract.get_max_comments_per_post());
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.set_max_comments_per_post(2);
+
+        contract.create_comment(0, "This is comment 1".to_string());
+        contract.reply(0, 0, "This is comment 2".to_string());
+        assert_eq!(2, contract.get_post_total_comments(0));
+
+        contract.create_comment(0, "This is comment 3".to_string());
+    }
+
+    #[test]
+    fn author_deletes_own_comment() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        set_caller(&mut context, "bob_near");
+        contract.create_comment(0, "This is bob's comment".to_string());
+        contract.create_comment(0, "This is bob's second comment".to_string());
+
+        contract.delete_my_comment(0, 0);
+
+        assert_eq!(vec![1], contract.get_post(0).unwrap().get_comments());
+        assert!(contract.get_comment(0, 0).is_none());
+        assert_eq!(1, contract.get_total_comments());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only author can delete the comment")]
+    fn non_author_cannot_delete_comment() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        set_caller(&mut context, "bob_near");
+        contract.create_comment(0, "This is bob's comment".to_string());
+
+        set_caller(&mut context, "carol_near");
+        contract.delete_my_comment(0, 0);
+    }
+
+    #[test]
+    fn get_single_comment() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(1, "This is the comment".to_string());
+
+        assert_eq!("This is the comment", contract.get_comment(1, 0).unwrap().get_body());
+        // the comment belongs to another post
+        assert!(contract.get_comment(0, 0).is_none());
+        assert!(contract.get_comment(1, 5).is_none());
+        assert!(contract.get_comment(9, 0).is_none());
+    }
+
+    #[test]
+    fn upvote_comment_toggles() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        assert_eq!(1, contract.upvote_comment(0, 0));
+        assert_eq!(0, contract.upvote_comment(0, 0));
+        assert_eq!(1, contract.upvote_comment(0, 0));
+        assert_eq!(1, contract.get_comment(0, 0).unwrap().get_upvotes().len());
+    }
+
+    #[test]
+    fn comments_sorted_by_votes() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        for i in 0..3 {
+            contract.create_comment(0, format!("This is comment {}", i));
+        }
+
+        // comment 2 gets two votes, comment 1 gets one
+        contract.upvote_comment(0, 2);
+        contract.upvote_comment(0, 1);
+        set_caller(&mut context, "bob_near");
+        contract.upvote_comment(0, 2);
+
+        let comment_ids: Vec<usize> = contract.get_comments_sorted_by_votes(0).iter().map(|comment| comment.get_comment_id()).collect();
+        assert_eq!(vec![2, 1, 0], comment_ids);
+    }
+
+    #[test]
+    fn author_can_edit_comment() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        let comment = contract.edit_comment(0, 0, "This is the edited comment".to_string());
+        assert_eq!("This is the edited comment".to_string(), comment.get_body());
+        assert_eq!("This is the edited comment".to_string(), contract.get_comment(0, 0).unwrap().get_body());
+    }
+
+    #[test]
+    fn edited_comment_is_flagged() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+        assert!(!contract.get_comment(0, 0).unwrap().is_edited());
+        assert_eq!(0, contract.get_comment(0, 0).unwrap().get_updated_at());
+
+        context.block_timestamp = 100;
+        set_caller(&mut context, "alice_near");
+        contract.edit_comment(0, 0, "This is the edited comment".to_string());
+
+        assert!(contract.get_comment(0, 0).unwrap().is_edited());
+        assert_eq!(100, contract.get_comment(0, 0).unwrap().get_updated_at());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only author can edit the comment")]
+    fn non_author_cannot_edit_comment() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        set_caller(&mut context, "bob_near");
+        contract.edit_comment(0, 0, "This is the edited comment".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Comment must be at least 10 characters long")]
+    fn edit_comment_too_short() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        contract.edit_comment(0, 0, "Too short".to_string());
+    }
+
+    #[test]
+    fn test_comments_paginated() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        for i in 0..5 {
+            contract.create_comment(0, format!("This is comment {}", i));
+        }
+        assert_eq!(5, contract.get_post_total_comments(0));
+
+        let comments = contract.get_comments_paginated(0, 1, 2);
+        assert_eq!(2, comments.len());
+        assert_eq!(1, comments[0].get_comment_id());
+        assert_eq!(2, comments[1].get_comment_id());
+
+        let comments = contract.get_comments_paginated(0, 4, 2);
+        assert_eq!(1, comments.len());
+        assert_eq!(4, comments[0].get_comment_id());
+
+        assert_eq!(0, contract.get_comments_paginated(0, 5, 2).len());
+
+        // second page of the page based view
+        let comments = contract.get_paging_comments(0, 2, 2);
+        assert_eq!(2, comments[0].get_comment_id());
+        assert_eq!(3, comments[1].get_comment_id());
+    }
+
+    #[test]
+    fn delete_middle_comment() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is comment 1, id 0".to_string());
+        contract.create_comment(0, "This is comment 2, id 1".to_string());
+        contract.create_comment(0, "This is comment 3, id 2".to_string());
+
+        contract.delete_comment(0, 1);
+
+        assert_eq!(vec![0, 2], contract.get_post(0).unwrap().get_comments());
+        assert_eq!(2, contract.get_post_total_comments(0));
+
+        let comments = contract.get_comments(0);
+        assert_eq!("This is comment 1, id 0".to_string(), comments[0].get_body());
+        assert_eq!("This is comment 3, id 2".to_string(), comments[1].get_body());
+    }
+
+    #[test]
+    #[should_panic(expected = "Comment does not exist")]
+    fn delete_missing_comment() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is comment 1, id 0".to_string());
+
+        contract.delete_comment(0, 5);
+    }
+
+    #[test]
+    fn upvote_test() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        // Create the first post
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        // Upvote the post
+        contract.upvote(0);
+
+        // Check if the upvote is there
+        assert_eq!(
+            1,
+            contract.get_post(0).unwrap().get_upvotes().len()
+        );
+
+        // upvote again to remove the upvote
+        assert_eq!(0, contract.upvote(0));
+        assert_eq!(0, contract.get_post(0).unwrap().get_upvotes().len());
+
+        // downvote flips a prior upvote
+        assert_eq!(1, contract.upvote(0));
+        assert_eq!(-1, contract.downvote(0));
+        assert_eq!(
+            (0, 1),
+            contract.get_votes_statistics(0)
+        );
+
+        // downvote again to remove the downvote
+        assert_eq!(0, contract.downvote(0));
+        assert_eq!(
+            (0, 0),
+            contract.get_votes_statistics(0)
+        );
+    }
+
+    #[test]
+    fn user_vote() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(0, contract.get_user_vote(0, "alice_near".to_string()));
+
+        contract.upvote(0);
+        assert_eq!(1, contract.get_user_vote(0, "alice_near".to_string()));
+
+        contract.downvote(0);
+        assert_eq!(-1, contract.get_user_vote(0, "alice_near".to_string()));
+        assert_eq!(0, contract.get_user_vote(0, "bob_near".to_string()));
+    }
+
+    fn sorted_post_ids(contract: &Blog, sort: &str) -> Vec<usize> {
+        contract.get_posts_sorted(sort.to_string(), 0, 10).iter().map(|post| post.get_post_id()).collect()
+    }
+
+    #[test]
+    fn test_posts_sorted() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+        contract.set_min_donation(U128(1));
+
+        for i in 0..3 {
+            contract.create_post(format!("This is the title {}", i), format!("Lets go Brandon! {}", i), None, None, None, None);
+        }
+
+        // post 1 is the most upvoted, post 0 is downvoted
+        contract.upvote(1);
+        contract.downvote(0);
+        set_caller(&mut context, "bob_near");
+        contract.upvote(1);
+        contract.upvote(2);
+
+        // post 0 gets the biggest donation
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Big donation".to_string());
+        context.attached_deposit = 10;
+        set_caller(&mut context, "bob_near");
+        contract.donate(2, "Small donation".to_string());
+
+        assert_eq!(vec![2, 1, 0], sorted_post_ids(&contract, "newest"));
+        assert_eq!(vec![0, 1, 2], sorted_post_ids(&contract, "oldest"));
+        assert_eq!(vec![1, 2, 0], sorted_post_ids(&contract, "top"));
+        assert_eq!(vec![0, 2, 1], sorted_post_ids(&contract, "donated"));
+
+        let posts = contract.get_posts_sorted("newest".to_string(), 1, 1);
+        assert_eq!(1, posts.len());
+        assert_eq!(1, posts[0].get_post_id());
+    }
+
+    // more posts than one mocked call may log events for, so the context is reset along the way
+    fn create_many_posts(context: &mut VMContext, contract: &mut Blog, count: usize) {
+        for i in 0..count {
+            if i % 50 == 0 {
+                context.storage_usage = env::storage_usage();
+                testing_env!(context.clone());
+            }
+            contract.create_post(format!("This is the title {}", i), "Lets go Brandon!".to_string(), None, None, None, None);
+        }
+    }
+
+    #[test]
+    fn test_posts_sorted_scans_newest_posts() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        create_many_posts(&mut context, &mut contract, MAX_SCANNED_POSTS + 1);
+
+        // post 0 falls outside the scan, the newest post does not
+        assert_eq!(vec![1, 2, 3], contract.get_posts_sorted("oldest".to_string(), 0, 3).iter().map(|post| post.get_post_id()).collect::<Vec<usize>>());
+        assert_eq!(MAX_SCANNED_POSTS, sorted_post_ids(&contract, "newest")[0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown sort")]
+    fn test_posts_sorted_unknown_sort() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let contract = Blog::default();
+
+        contract.get_posts_sorted("random".to_string(), 0, 10);
+    }
+
+    #[test]
+    fn trending_posts_decay_with_age() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        let hour: u64 = 3_600_000_000_000;
+
+        // an old post with many votes
+        contract.create_post("This is the old title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        for voter in ["alice_near", "bob_near", "carol_near", "dave_near", "erin_near"].iter() {
+            set_caller(&mut context, voter);
+            contract.vote(0, 1);
+        }
+
+        // two days later, a new post with a few votes
+        context.block_timestamp = 48 * hour;
+        set_caller(&mut context, "alice_near");
+        contract.create_post("This is the new title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.vote(1, 1);
+        set_caller(&mut context, "bob_near");
+        contract.vote(1, 1);
+
+        context.block_timestamp = 50 * hour;
+        set_caller(&mut context, "alice_near");
+        let trending: Vec<usize> = contract.get_trending(10, None).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![1, 0], trending);
+
+        // at the same age the more engaging post wins
+        context.block_timestamp = 0;
+        set_caller(&mut context, "alice_near");
+        let trending: Vec<usize> = contract.get_trending(1, None).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![0], trending);
+    }
+
+    #[test]
+    fn test_search_posts() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("Learning Rust".to_string(), "Ownership and borrowing".to_string(), None, None, None, None);
+        contract.create_post("Cooking".to_string(), "A recipe with RUST colored peppers".to_string(), None, None, None, None);
+        contract.create_post("Gardening".to_string(), "Tomatoes".to_string(), None, None, None, None);
+        contract.delete_post(0, None);
+
+        let posts = contract.search_posts("rust".to_string(), 0, 10);
+        assert_eq!(1, posts.len());
+        assert_eq!(1, posts[0].get_post_id());
+
+        assert_eq!(1, contract.search_posts("TOMATO".to_string(), 0, 10).len());
+        assert_eq!(0, contract.search_posts("near".to_string(), 0, 10).len());
+    }
+
+    #[test]
+    fn test_search_posts_scans_newest_posts() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        create_many_posts(&mut context, &mut contract, MAX_SCANNED_POSTS + 1);
+
+        let posts = contract.search_posts(format!("title {}", MAX_SCANNED_POSTS), 0, 10);
+        assert_eq!(1, posts.len());
+        assert_eq!(MAX_SCANNED_POSTS, posts[0].get_post_id());
+        assert!(contract.search_posts("title 0".to_string(), 0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_paging_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        // Loop 100 post and create them
+        for i in 0..45 {
+            contract.create_post(format!("This is the title {}", i), format!("Lets go Brandon! {}", i), None, None, None, None);
+        }
+
+        assert_eq!(45, contract.get_total_posts(), "Total post is not 45");
+        
+        // Check paging post call
+        // let posts = contract.get_paging_posts(1, 10);
+        // assert_eq!(10, posts.len(), "Paging post size is not 10");
+        // assert_eq!(0, posts[0].get_post_id(), "Paging post id is not 10");
+        // assert_eq!(9, posts[9].get_post_id(), "Paging post id is not 20");
+
+        // Check paging post call
+        let posts = contract.get_paging_posts(5, 10);
+
+        assert_eq!(5, posts.len(), "Paging post size is not 5");
+        assert_eq!(40, posts[0].get_post_id(), "Paging post id is not 40");
+        assert_eq!(44, posts[4].get_post_id(), "Paging post id is not 44");
+    }
+
+    #[test]
+    fn test_posts_paginated() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        for i in 0..10 {
+            contract.create_post(format!("This is the title {}", i), format!("Lets go Brandon! {}", i), None, None, None, None);
+        }
+
+        let posts = contract.get_posts_paginated("alice_near".to_string(), 0, 4);
+        assert_eq!(4, posts.len());
+        assert_eq!(0, posts[0].get_post_id());
+        assert_eq!(3, posts[3].get_post_id());
+
+        // last page is cut short
+        let posts = contract.get_posts_paginated("alice_near".to_string(), 8, 4);
+        assert_eq!(2, posts.len());
+        assert_eq!(8, posts[0].get_post_id());
+        assert_eq!(9, posts[1].get_post_id());
+
+        // past the end
+        assert_eq!(0, contract.get_posts_paginated("alice_near".to_string(), 10, 4).len());
+    }
+
+    #[test]
+    fn test_posts_paginated_clamps_limit() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        for i in 0..60 {
+            // start a new call every 30 posts to stay under the log limit
+            if i == 30 {
+                set_caller(&mut context, "alice_near");
+            }
+            contract.create_post(format!("This is the title {}", i), format!("Lets go Brandon! {}", i), None, None, None, None);
+        }
+
+        let posts = contract.get_posts_paginated("alice_near".to_string(), 0, 1000);
+        assert_eq!(MAX_PAGE_LIMIT, posts.len());
+        assert_eq!(49, posts[49].get_post_id());
+    }
+
+    #[test]
+    fn test_all_posts() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        // posts from several authors
+        for author in ["alice_near", "bob_near", "carol_near"].iter() {
+            set_caller(&mut context, author);
+            contract.create_post(format!("Post by {}", author), "Lets go Brandon!".to_string(), None, None, None, None);
+            contract.create_post(format!("Post by {}", author), "Lets go Brandon!".to_string(), None, None, None, None);
+        }
+
+        // owner removes a couple of them
+        set_caller(&mut context, "alice_near");
+        contract.delete_post(1, None);
+        contract.delete_post(4, None);
+
+        let posts = contract.get_all_posts(0, 10, None, None);
+        let post_ids: Vec<usize> = posts.iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![0, 2, 3, 5], post_ids);
+        assert_eq!("bob_near".to_string(), posts[1].get_author());
+
+        // from_index counts listed posts, not ids
+        let posts = contract.get_all_posts(2, 2, None, None);
+        let post_ids: Vec<usize> = posts.iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![3, 5], post_ids);
+
+        assert_eq!(0, contract.get_all_posts(4, 10, None, None).len());
+    }
+
+    #[test]
+    fn test_donation() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        // Create the first post
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        // Donate with an attached deposit
+        context.attached_deposit = 1000000;
+        context.storage_usage = env::storage_usage();
+        testing_env!(context);
+        contract.donate(0, "Support Trump for the USA".to_string());
+
+        // Check if the donation is there
+        assert_eq!(
+            1000000,
+            contract.get_post(0).unwrap().get_total_donation()
+        );
+    }
+
+    #[test]
+    fn test_donation_counters() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_donation_cooldown(0);
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        context.attached_deposit = 1000000;
+        context.storage_usage = env::storage_usage();
+        testing_env!(context);
+        contract.donate(0, "First donation".to_string());
+        contract.donate(0, "Second donation".to_string());
+
+        assert_eq!(2, contract.get_next_donation_id());
+        assert_eq!(2, contract.get_total_donations());
+
+        // comment counters are left alone
+        assert_eq!(1, contract.get_next_comment_id());
+        assert_eq!(1, contract.get_total_comments());
+    }
+
+    #[test]
+    fn test_top_donors() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_donation_cooldown(0);
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        for (donor, amount) in [("bob_near", 100), ("carol_near", 300), ("bob_near", 150), ("carol_near", 50)].iter() {
+            context.attached_deposit = *amount;
+            set_caller(&mut context, donor);
+            contract.donate(0, "Donation".to_string());
+        }
+
+        assert_eq!(600, contract.get_post(0).unwrap().get_total_donation());
+
+        let top_donors = contract.get_top_donors(0, 10);
+        assert_eq!(vec![("carol_near".to_string(), U128(350)), ("bob_near".to_string(), U128(250))], top_donors);
+
+        assert_eq!(1, contract.get_top_donors(0, 1).len());
+    }
+
+    #[test]
+    fn test_donation_amount_json_round_trip() {
+        // above 2^53, where JSON numbers lose precision
+        let amount: u128 = 9_007_199_254_740_993;
+        let donation_log = DonationLog::new(0, amount, "bob_near".to_string(), 0, "Donation".to_string(), 0, None);
+
+        let json = near_sdk::serde_json::to_string(&donation_log).unwrap();
+        assert!(json.contains("\"amount\":\"9007199254740993\""));
+
+        let donation_log: DonationLog = near_sdk::serde_json::from_str(&json).unwrap();
+        assert_eq!(amount, donation_log.get_amount());
+    }
+
+    #[test]
+    fn create_post_with_nonce_is_idempotent() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        let post_id = contract.create_post_with_nonce("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None, "retry-1".to_string());
+        assert_eq!(0, post_id);
+
+        // a retry with the same nonce doesn't hit the cooldown and creates nothing
+        let post_id = contract.create_post_with_nonce("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None, "retry-1".to_string());
+        assert_eq!(0, post_id);
+        assert_eq!(1, contract.get_total_posts());
+
+        // nonces are per author
+        set_caller(&mut context, "bob_near");
+        let post_id = contract.create_post_with_nonce("This is bob's title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None, "retry-1".to_string());
+        assert_eq!(1, post_id);
+        assert_eq!(2, contract.get_total_posts());
+    }
+
+    #[test]
+    fn delete_post_cleans_up_indexes() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post_with_nonce("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None, "retry-1".to_string());
+        contract.create_comment(0, "This is the comment".to_string());
+        contract.feature_post(0);
+        contract.report_post(0, "Spam".to_string());
+        contract.delete_post(0, None);
+
+        assert_eq!(0, contract.get_stats().total_comments);
+        assert!(contract.get_comments_by_author("alice_near".to_string(), 0, 10).is_empty());
+        assert!(contract.featured.is_empty());
+        assert!(contract.reports.get(&0).is_none());
+
+        // the nonce is free again
+        let post_id = contract.create_post_with_nonce("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None, "retry-1".to_string());
+        assert_eq!(1, post_id);
+    }
+
+    #[test]
+    fn create_post_with_nonce_excerpt_and_cover_image() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post_with_nonce(
+            "This is the title".to_string(),
+            "Lets go Brandon!".to_string(),
+            None,
+            None,
+            Some("https://example.com/cover.png".to_string()),
+            Some("A short summary".to_string()),
+            "retry-1".to_string(),
+        );
+        let post = contract.get_post(0).unwrap();
+        assert_eq!("A short summary", post.get_excerpt());
+        assert_eq!("https://example.com/cover.png", post.get_cover_image_url());
+    }
+
+    #[test]
+    #[should_panic(expected = "Excerpt must be at most 300 characters long")]
+    fn create_post_with_nonce_excerpt_too_long() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post_with_nonce("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, Some("a".repeat(MAX_EXCERPT_LENGTH + 1)), "retry-1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Nonce must be at most 64 bytes long")]
+    fn create_post_with_nonce_too_long() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post_with_nonce("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None, "n".repeat(65));
+    }
+
+    #[test]
+    fn posts_by_ids() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_posts_batch(vec![
+            ("First post".to_string(), "Lets go Brandon!".to_string()),
+            ("Second post".to_string(), "Lets go Brandon!".to_string()),
+            ("Third post".to_string(), "Lets go Brandon!".to_string()),
+        ]);
+        contract.delete_post(1, None);
+
+        let posts = contract.get_posts_by_ids(vec![2, 1, 0, 7]);
+        let post_ids: Vec<Option<usize>> = posts.iter().map(|post| post.as_ref().map(|post| post.get_post_id())).collect();
+        assert_eq!(vec![Some(2), None, Some(0), None], post_ids);
+    }
+
+    #[test]
+    fn recompute_totals() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+        contract.create_comment(1, "This comment gets orphaned".to_string());
+        // the way delete_post removed posts before it dropped their comments
+        contract.posts.remove(&1);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Donation".to_string());
+
+        // knock the derived state out of sync
+        contract.total_donations = 7;
+        contract.user_posts.insert(&"bob_near".to_string(), &vec![5]);
+        assert_eq!(2, contract.get_total_comments());
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        let stats = contract.recompute_totals();
+
+        assert_eq!(1, stats.total_posts);
+        assert_eq!(1, stats.total_comments);
+        assert_eq!(1, stats.total_donations);
+        assert_eq!(1, stats.total_authors);
+        assert_eq!(1, contract.get_user_posts("alice_near".to_string()).len());
+        assert!(contract.get_user_posts("bob_near".to_string()).is_empty());
+    }
+
+    #[test]
+    fn purge_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is alice's title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        for i in 0..3 {
+            contract.create_post(format!("Spam number {}", i), "Lets go Brandon!".to_string(), Some(vec!["spam".to_string()]), None, None, None);
+        }
+        assert_eq!(4, contract.get_total_posts());
+
+        set_caller(&mut context, "alice_near");
+        assert_eq!(3, contract.purge_author("bob_near".to_string(), None));
+
+        assert_eq!(1, contract.get_total_posts());
+        assert!(contract.get_posts_by_author("bob_near".to_string(), 0, 10).is_empty());
+        assert!(contract.get_posts_by_tag("spam".to_string(), 0, 10).is_empty());
+        assert_eq!(1, contract.get_author_count());
+    }
+
+    #[test]
+    fn purge_author_skips_missing_posts() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        set_caller(&mut context, "bob_near");
+        contract.create_post("Spam number 0".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        // a stale id, as left behind by the old delete_post
+        contract.user_posts.insert(&"bob_near".to_string(), &vec![7, 0]);
+
+        set_caller(&mut context, "alice_near");
+        assert_eq!(1, contract.purge_author("bob_near".to_string(), None));
+        assert_eq!(0, contract.get_total_posts());
+        assert!(contract.user_posts.get(&"bob_near".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Post has donations, soft delete it instead")]
+    fn purge_author_with_donations() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is bob's title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = DEFAULT_MIN_DONATION;
+        set_caller(&mut context, "carol_near");
+        contract.donate(0, "Donation".to_string());
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        contract.purge_author("bob_near".to_string(), None);
+    }
+
+    #[test]
+    fn force_purge_author_with_donations() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is bob's title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = DEFAULT_MIN_DONATION;
+        set_caller(&mut context, "carol_near");
+        contract.donate(0, "Donation".to_string());
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        assert_eq!(1, contract.purge_author("bob_near".to_string(), Some(true)));
+        assert!(contract.get_post(0).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can purge an author")]
+    fn purge_author_requires_owner() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        set_caller(&mut context, "bob_near");
+        contract.purge_author("alice_near".to_string(), None);
+    }
+
+    #[test]
+    fn posts_count() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        assert_eq!(0, contract.get_posts_count("bob_near".to_string()));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_draft("This is the draft".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(3, contract.get_posts_count("alice_near".to_string()));
+    }
+
+    #[test]
+    fn list_authors() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        for author in ["alice_near", "bob_near", "carol_near", "bob_near"].iter() {
+            set_caller(&mut context, author);
+            contract.create_post(format!("Post by {}", author), "Lets go Brandon!".to_string(), None, None, None, None);
+        }
+
+        assert_eq!(3, contract.get_author_count());
+
+        let mut authors = contract.get_authors(0, 10);
+        authors.sort();
+        assert_eq!(vec!["alice_near".to_string(), "bob_near".to_string(), "carol_near".to_string()], authors);
+
+        assert_eq!(2, contract.get_authors(1, 10).len());
+        assert_eq!(1, contract.get_authors(0, 1).len());
+    }
+
+    #[test]
+    fn unpublish_and_republish_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        contract.unpublish_post(0);
+        assert!(contract.get_all_posts(0, 10, None, None).is_empty());
+        assert!(!contract.get_post(0).unwrap().is_published());
+
+        contract.publish_post(0);
+        assert_eq!(1, contract.get_all_posts(0, 10, None, None).len());
+        assert_eq!(1, contract.get_post_total_comments(0));
+        assert_eq!("This is the comment", contract.get_comments(0)[0].get_body());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only author can unpublish the post")]
+    fn unpublish_post_requires_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.unpublish_post(0);
+    }
+
+    #[test]
+    fn comment_length_counts_characters() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        // 10 characters but 40 bytes
+        contract.create_comment(0, "🚀".repeat(10));
+        assert_eq!("🚀".repeat(10), contract.get_comment(0, 0).unwrap().get_body());
+
+        contract.set_comment_length_limits(2, 5);
+        assert_eq!((2, 5), contract.get_comment_length_limits());
+        contract.create_comment(0, "Nice!".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Comment must be at most 1000 characters long")]
+    fn comment_too_long() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "a".repeat(1001));
+    }
+
+    #[test]
+    fn moderation_queue() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        for i in 0..4 {
+            contract.create_post(format!("This is post {}", i), "Lets go Brandon!".to_string(), None, None, None, None);
+        }
+        contract.set_nsfw(3, true);
+
+        set_caller(&mut context, "bob_near");
+        contract.report_post(0, "Spam".to_string());
+        contract.report_post(2, "Spam".to_string());
+        set_caller(&mut context, "carol_near");
+        contract.report_post(2, "Offensive".to_string());
+
+        set_caller(&mut context, "alice_near");
+        let queue: Vec<usize> = contract.get_moderation_queue(0, 10).iter().map(|view| view.post.get_post_id()).collect();
+        assert_eq!(vec![2, 0, 3], queue);
+
+        let page: Vec<usize> = contract.get_moderation_queue(1, 1).iter().map(|view| view.post.get_post_id()).collect();
+        assert_eq!(vec![0], page);
+    }
+
+    #[test]
+    fn moderation_queue_scans_newest_posts() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        create_many_posts(&mut context, &mut contract, MAX_SCANNED_POSTS + 1);
+        contract.set_nsfw(0, true);
+        contract.set_nsfw(MAX_SCANNED_POSTS, true);
+
+        let queue: Vec<usize> = contract.get_moderation_queue(0, 10).iter().map(|view| view.post.get_post_id()).collect();
+        assert_eq!(vec![MAX_SCANNED_POSTS], queue);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner or moderators can view the moderation queue")]
+    fn moderation_queue_is_moderator_only() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let contract = Blog::default();
+
+        set_caller(&mut context, "bob_near");
+        contract.get_moderation_queue(0, 10);
+    }
+
+    #[test]
+    fn post_view_counts() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+        contract.create_comment(0, "This is another comment".to_string());
+        contract.vote(0, 1);
+
+        set_caller(&mut context, "bob_near");
+        contract.vote(0, 1);
+        set_caller(&mut context, "carol_near");
+        contract.vote(0, -1);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "dave_near");
+        contract.donate(0, "Donation".to_string());
+
+        let view = contract.get_post_view(0);
+        assert_eq!(0, view.post.get_post_id());
+        assert_eq!(1, view.net_score);
+        assert_eq!(2, view.upvote_count);
+        assert_eq!(1, view.downvote_count);
+        assert_eq!(2, view.comment_count);
+        assert_eq!(U128(1000), view.total_donated);
+    }
+
+    #[test]
+    fn toggle_reactions() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.react(0, "like".to_string());
+        set_caller(&mut context, "bob_near");
+        contract.react(0, "like".to_string());
+
+        let count = |contract: &Blog, reaction: &str| {
+            contract.get_reactions(0).into_iter().find(|(x, _)| x == reaction).unwrap().1
+        };
+        assert_eq!(2, count(&contract, "like"));
+        assert_eq!(4, contract.get_reactions(0).len());
+
+        // a different reaction switches
+        contract.react(0, "love".to_string());
+        assert_eq!(1, count(&contract, "like"));
+        assert_eq!(1, count(&contract, "love"));
+
+        // the same reaction again removes it
+        contract.react(0, "love".to_string());
+        assert_eq!(0, count(&contract, "love"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Reaction must be one of like, love, laugh, insightful")]
+    fn react_rejects_unknown_reaction() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.react(0, "angry".to_string());
+    }
+
+    #[test]
+    fn combined_vote() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        let assert_no_overlap = |contract: &Blog| {
+            let post = contract.get_post(0).unwrap();
+            assert!(post.get_upvotes().is_disjoint(&post.get_downvotes()));
+        };
+
+        assert_eq!(1, contract.vote(0, 1));
+        // voting the same way again is a no-op
+        assert_eq!(1, contract.vote(0, 1));
+        assert_no_overlap(&contract);
+
+        assert_eq!(-1, contract.vote(0, -1));
+        assert_no_overlap(&contract);
+
+        assert_eq!(0, contract.vote(0, 0));
+        assert_no_overlap(&contract);
+        assert_eq!((0, 0), contract.get_votes_statistics(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Direction must be -1, 0 or 1")]
+    fn vote_rejects_invalid_direction() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.vote(0, 2);
+    }
+
+    #[test]
+    fn post_excerpt_and_cover_image() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post(
+            "This is the title".to_string(),
+            "Lets go Brandon!".to_string(),
+            None,
+            None,
+            Some("https://example.com/cover.png".to_string()),
+            Some("A short summary".to_string()),
+        );
+        let post = contract.get_post(0).unwrap();
+        assert_eq!("A short summary", post.get_excerpt());
+        assert_eq!("https://example.com/cover.png", post.get_cover_image_url());
+
+        // without an excerpt the start of the body is used
+        let body = "b".repeat(200);
+        contract.create_post("This is the second title".to_string(), body, None, None, None, None);
+        let post = contract.get_post(1).unwrap();
+        assert_eq!("b".repeat(160), post.get_excerpt());
+        assert_eq!("", post.get_cover_image_url());
+    }
+
+    #[test]
+    fn edit_post_updates_derived_excerpt() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, Some("A short summary".to_string()));
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        contract.edit_post(0, "This is the title".to_string(), "A new body".to_string());
+        contract.edit_post(1, "This is the second title".to_string(), "A new body".to_string());
+
+        // a written excerpt is kept, a derived one follows the body
+        assert_eq!("A short summary", contract.get_post(0).unwrap().get_excerpt());
+        assert_eq!("A new body", contract.get_post(1).unwrap().get_excerpt());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cover image url must be at most 500 bytes long")]
+    fn post_cover_image_url_too_long() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, Some("u".repeat(501)), None);
+    }
+
+    #[test]
+    fn nsfw_posts() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the nsfw title".to_string(), "Lets go Brandon!".to_string(), None, Some(true), None, None);
+        assert!(!contract.get_post(0).unwrap().is_nsfw());
+        assert!(contract.get_post(1).unwrap().is_nsfw());
+
+        let post_ids = |posts: Vec<Post>| posts.iter().map(|post| post.get_post_id()).collect::<Vec<usize>>();
+        assert_eq!(vec![0, 1], post_ids(contract.get_all_posts(0, 10, None, None)));
+        assert_eq!(vec![0, 1], post_ids(contract.get_all_posts(0, 10, Some(true), None)));
+        assert_eq!(vec![0], post_ids(contract.get_all_posts(0, 10, Some(false), None)));
+
+        // moderators can flag other authors' posts
+        contract.add_moderator("bob_near".to_string());
+        set_caller(&mut context, "bob_near");
+        contract.set_nsfw(0, true);
+        contract.set_nsfw(1, false);
+        assert_eq!(vec![1], post_ids(contract.get_all_posts(0, 10, Some(false), None)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only author, owner or moderators can flag the post")]
+    fn set_nsfw_requires_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.set_nsfw(0, true);
+    }
+
+    #[test]
+    fn test_total_pending_donations() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_donation_cooldown(0);
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is bob's title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "carol_near");
+        contract.donate(0, "Donation".to_string());
+        contract.donate(1, "Donation".to_string());
+        contract.donate(1, "Donation".to_string());
+        assert_eq!(U128(3000), contract.get_total_pending_donations());
+
+        // withdrawn donations are no longer pending
+        context.attached_deposit = 0;
+        set_caller(&mut context, "bob_near");
+        contract.withdraw_donations();
+        assert_eq!(U128(1000), contract.get_total_pending_donations());
+
+        assert_eq!(U128(env::account_balance()), contract.get_contract_balance());
+    }
+
+    #[test]
+    fn test_donation_cooldown() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+        contract.set_post_cooldown(0);
+        assert_eq!(10, contract.get_donation_cooldown());
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "First donation".to_string());
+        // the cooldown is per post
+        contract.donate(1, "Other post".to_string());
+
+        context.block_timestamp = 11_000_000_000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Second donation".to_string());
+
+        assert_eq!(2, contract.get_donation_count(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Please wait 7 seconds before donating to this post again")]
+    fn test_donation_cooldown_rejects_rapid_donations() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "First donation".to_string());
+
+        context.block_timestamp = 3_000_000_000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Second donation".to_string());
+    }
+
+    #[test]
+    fn test_ft_donation() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.add_accepted_token("usdc_near".to_string(), U128(100));
+
+        // the token contract calls ft_on_transfer
+        context.attached_deposit = 0;
+        set_caller(&mut context, "usdc_near");
+        let unused = contract.ft_on_transfer("bob_near".to_string(), U128(500), r#"{"post_id": 0, "message": "Have some tokens"}"#.to_string());
+        assert_eq!(U128(0), unused);
+
+        let donation_logs = contract.get_donations(0, 0, 10);
+        assert_eq!(1, donation_logs.len());
+        assert_eq!(Some("usdc_near".to_string()), donation_logs[0].get_token());
+        assert_eq!("bob_near", donation_logs[0].get_donor());
+        assert_eq!(500, donation_logs[0].get_amount());
+        assert_eq!("Have some tokens", donation_logs[0].get_message());
+
+        // tokens are tracked apart from NEAR
+        assert_eq!(U128(500), contract.get_pending_ft_donations("alice_near".to_string(), "usdc_near".to_string()));
+        assert_eq!(U128(0), contract.get_pending_donations("alice_near".to_string()));
+        assert_eq!(0, contract.get_post(0).unwrap().get_total_donation());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid msg")]
+    fn test_ft_donation_invalid_msg() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.add_accepted_token("usdc_near".to_string(), U128(100));
+
+        set_caller(&mut context, "usdc_near");
+        contract.ft_on_transfer("bob_near".to_string(), U128(500), "post 0".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is not accepted")]
+    fn test_ft_donation_from_unknown_token() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.add_accepted_token("usdc_near".to_string(), U128(100));
+
+        // anyone calling ft_on_transfer directly is not a token contract
+        set_caller(&mut context, "mallory_near");
+        contract.ft_on_transfer("bob_near".to_string(), U128(500), r#"{"post_id": 0}"#.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Donation must be at least 100 of this token")]
+    fn test_ft_donation_below_minimum() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.add_accepted_token("usdc_near".to_string(), U128(100));
+
+        set_caller(&mut context, "usdc_near");
+        contract.ft_on_transfer("bob_near".to_string(), U128(99), r#"{"post_id": 0}"#.to_string());
+    }
+
+    #[test]
+    fn test_withdraw_ft_donations() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.add_accepted_token("usdc_near".to_string(), U128(100));
+
+        set_caller(&mut context, "usdc_near");
+        contract.ft_on_transfer("bob_near".to_string(), U128(500), r#"{"post_id": 0}"#.to_string());
+
+        set_caller(&mut context, "alice_near");
+        contract.withdraw_ft_donations("usdc_near".to_string());
+        assert_eq!(U128(0), contract.get_pending_ft_donations("alice_near".to_string(), "usdc_near".to_string()));
+    }
+
+    #[test]
+    fn test_failed_ft_withdraw_is_credited_back() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        // the callback runs as the contract itself, with a failed ft_transfer
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context, VMConfig::default(), RuntimeFeesConfig::default(), Default::default(), vec![PromiseResult::Failed]);
+
+        assert!(!contract.on_ft_withdraw_complete("alice_near".to_string(), "usdc_near".to_string(), U128(500)));
+        assert_eq!(U128(500), contract.get_pending_ft_donations("alice_near".to_string(), "usdc_near".to_string()));
+    }
+
+    #[test]
+    fn test_successful_ft_withdraw_is_not_credited_back() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context, VMConfig::default(), RuntimeFeesConfig::default(), Default::default(), vec![PromiseResult::Successful(vec![])]);
+
+        assert!(contract.on_ft_withdraw_complete("alice_near".to_string(), "usdc_near".to_string(), U128(500)));
+        assert_eq!(U128(0), contract.get_pending_ft_donations("alice_near".to_string(), "usdc_near".to_string()));
+    }
+
+    #[test]
+    fn test_donation_fee() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+        contract.set_donation_fee(500);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Donation".to_string());
+
+        let donation_log = &contract.get_donations(0, 0, 1)[0];
+        assert_eq!(1000, donation_log.get_amount());
+        assert_eq!(950, donation_log.get_net_amount());
+        assert_eq!(U128(950), contract.get_pending_donations("alice_near".to_string()));
+        assert_eq!(U128(50), contract.get_collected_fees());
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        contract.withdraw_fees(U128(50));
+        assert_eq!(U128(0), contract.get_collected_fees());
+    }
+
+    #[test]
+    fn test_failed_fee_withdraw_is_credited_back() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        // the callback runs as the contract itself, with a failed transfer
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context, VMConfig::default(), RuntimeFeesConfig::default(), Default::default(), vec![PromiseResult::Failed]);
+
+        assert!(!contract.on_fee_withdraw_complete(U128(50)));
+        assert_eq!(U128(50), contract.get_collected_fees());
+    }
+
+    #[test]
+    #[should_panic(expected = "Donation fee must be at most 1000 basis points")]
+    fn test_donation_fee_is_capped() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.set_donation_fee(1001);
+    }
+
+    #[test]
+    fn test_donate_split() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is bob's title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "carol_near");
+        contract.donate_split(vec![(0, U128(300)), (1, U128(700))], "Thanks both".to_string());
+
+        assert_eq!(300, contract.get_post(0).unwrap().get_total_donation());
+        assert_eq!(700, contract.get_post(1).unwrap().get_total_donation());
+        assert_eq!(U128(300), contract.get_pending_donations("alice_near".to_string()));
+        assert_eq!(U128(700), contract.get_pending_donations("bob_near".to_string()));
+        assert_eq!(2, contract.get_total_donations());
+    }
+
+    #[test]
+    #[should_panic(expected = "Each post can appear only once in the allocations")]
+    fn test_donate_split_duplicate_post() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate_split(vec![(0, U128(500)), (0, U128(500))], "Split".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Allocations must add up to the attached deposit")]
+    fn test_donate_split_mismatch() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "carol_near");
+        contract.donate_split(vec![(0, U128(300)), (0, U128(600))], "Thanks".to_string());
+    }
+
+    #[test]
+    fn test_top_donors_global() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        for (donor, post_id, amount) in [("bob_near", 0, 100), ("carol_near", 1, 500), ("dave_near", 0, 50), ("bob_near", 1, 300)].iter() {
+            context.attached_deposit = *amount;
+            set_caller(&mut context, donor);
+            contract.donate(*post_id, "Donation".to_string());
+        }
+
+        let leaderboard = contract.get_top_donors_global(10);
+        assert_eq!(vec![
+            ("carol_near".to_string(), U128(500)),
+            ("bob_near".to_string(), U128(400)),
+            ("dave_near".to_string(), U128(50)),
+        ], leaderboard);
+
+        assert_eq!(vec![("carol_near".to_string(), U128(500))], contract.get_top_donors_global(1));
+    }
+
+    #[test]
+    fn test_get_donations_paginated() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_donation_cooldown(0);
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(0, contract.get_donation_count(0));
+
+        for amount in [100, 200, 300, 400].iter() {
+            context.attached_deposit = *amount;
+            set_caller(&mut context, "bob_near");
+            contract.donate(0, "Donation".to_string());
+        }
+
+        assert_eq!(4, contract.get_donation_count(0));
+
+        let amounts: Vec<u128> = contract.get_donations(0, 1, 2).iter().map(|donation_log| donation_log.get_amount()).collect();
+        assert_eq!(vec![200, 300], amounts);
+        assert_eq!(1, contract.get_donations(0, 3, 10).len());
+        assert!(contract.get_donations(0, 4, 10).is_empty());
+    }
+
+    #[test]
+    fn test_redact_donation_message() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_donation_cooldown(0);
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Some abusive message".to_string());
+        contract.donate(0, "A nice message".to_string());
+
+        set_caller(&mut context, "alice_near");
+        contract.redact_donation_message(0, 0);
+
+        let donation_logs = contract.get_post(0).unwrap().get_donation_logs();
+        assert_eq!("".to_string(), donation_logs[0].get_message());
+        assert_eq!(1000, donation_logs[0].get_amount());
+        assert_eq!("bob_near".to_string(), donation_logs[0].get_donor());
+        assert_eq!("A nice message".to_string(), donation_logs[1].get_message());
+        assert_eq!(2000, contract.get_post(0).unwrap().get_total_donation());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner or moderators can redact donations")]
+    fn test_redact_donation_message_requires_moderator() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Some abusive message".to_string());
+        contract.redact_donation_message(0, 0);
+    }
+
+    #[test]
+    fn soft_delete_and_restore_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_posts_batch(vec![
+            ("First post".to_string(), "Lets go Brandon!".to_string()),
+            ("Second post".to_string(), "Lets go Brandon!".to_string()),
+        ]);
+
+        contract.soft_delete_post(0);
+
+        // hidden from feeds and search
+        let feed: Vec<usize> = contract.get_all_posts(0, 10, None, None).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![1], feed);
+        assert!(contract.search_posts("first".to_string(), 0, 10).is_empty());
+
+        // still retrievable with the flag set
+        let post = contract.get_post(0).unwrap();
+        assert!(post.is_deleted());
+        assert_eq!("First post", post.get_title());
+
+        contract.restore_post(0);
+        assert!(!contract.get_post(0).unwrap().is_deleted());
+        assert_eq!(2, contract.get_all_posts(0, 10, None, None).len());
+    }
+
+    #[test]
+    fn user_posts_leave_out_drafts_and_deleted_posts() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        for i in 0..3 {
+            contract.create_post(format!("This is title {}", i), "Lets go Brandon!".to_string(), None, None, None, None);
+        }
+        contract.soft_delete_post(0);
+        contract.unpublish_post(1);
+
+        let post_ids: Vec<usize> = contract.get_user_posts("alice_near".to_string()).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![2], post_ids);
+    }
+
+    #[test]
+    #[should_panic(expected = "Post is deleted")]
+    fn soft_deleted_post_rejects_comments() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.soft_delete_post(0);
+        contract.create_comment(0, "Still here?".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Post is deleted")]
+    fn soft_deleted_post_rejects_votes() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.soft_delete_post(0);
+        contract.upvote(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Post is deleted")]
+    fn soft_deleted_post_rejects_donations() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.soft_delete_post(0);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Thanks!".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only author, owner or moderators can delete the post")]
+    fn soft_delete_post_requires_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.soft_delete_post(0);
+    }
+
+    #[test]
+    fn posts_by_date_range() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        for (i, timestamp) in [100, 200, 300, 400].iter().enumerate() {
+            context.block_timestamp = *timestamp;
+            set_caller(&mut context, "alice_near");
+            contract.create_post(format!("Post number {}", i), "Lets go Brandon!".to_string(), None, None, None, None);
+        }
+
+        let post_ids = |posts: Vec<Post>| posts.iter().map(|post| post.get_post_id()).collect::<Vec<usize>>();
+
+        // start is inclusive, end is exclusive
+        assert_eq!(vec![1, 2], post_ids(contract.get_posts_by_date_range(200, 400, 0, 10)));
+        assert_eq!(vec![0, 1, 2, 3], post_ids(contract.get_posts_by_date_range(0, 401, 0, 10)));
+        assert_eq!(Vec::<usize>::new(), post_ids(contract.get_posts_by_date_range(200, 200, 0, 10)));
+        assert_eq!(vec![2], post_ids(contract.get_posts_by_date_range(200, 400, 1, 1)));
+        assert_eq!(Vec::<usize>::new(), post_ids(contract.get_posts_by_date_range(500, 600, 0, 10)));
+
+        // the search for the start steps over removed ids
+        contract.delete_post(1, None);
+        assert_eq!(vec![2, 3], post_ids(contract.get_posts_by_date_range(150, 401, 0, 10)));
+        assert_eq!(vec![0], post_ids(contract.get_posts_by_date_range(0, 250, 0, 10)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Start must not be after end")]
+    fn posts_by_date_range_rejects_inverted_range() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let contract = Blog::default();
+
+        contract.get_posts_by_date_range(400, 200, 0, 10);
+    }
+
+    #[test]
+    fn feature_and_unfeature_posts() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_posts_batch(vec![
+            ("First post".to_string(), "Lets go Brandon!".to_string()),
+            ("Second post".to_string(), "Lets go Brandon!".to_string()),
+            ("Third post".to_string(), "Lets go Brandon!".to_string()),
+        ]);
+
+        contract.feature_post(2);
+        contract.feature_post(0);
+        let featured: Vec<usize> = contract.get_featured_posts().iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![2, 0], featured);
+
+        contract.unfeature_post(2);
+        let featured: Vec<usize> = contract.get_featured_posts().iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![0], featured);
+
+        // deleted posts are skipped
+        contract.feature_post(1);
+        contract.delete_post(0, None);
+        let featured: Vec<usize> = contract.get_featured_posts().iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![1], featured);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner or moderators can feature posts")]
+    fn feature_post_requires_moderator() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.feature_post(0);
+    }
+
+    #[test]
+    fn create_posts_batch() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        let post_ids = contract.create_posts_batch(vec![
+            ("First imported post".to_string(), "Lets go Brandon!".to_string()),
+            ("Second imported post".to_string(), "Lets go Brandon!".to_string()),
+            ("Third imported post".to_string(), "Lets go Brandon!".to_string()),
+        ]);
+
+        assert_eq!(vec![0, 1, 2], post_ids);
+        assert_eq!(3, contract.get_total_posts());
+        assert_eq!("Second imported post", contract.get_post(1).unwrap().get_title());
+        assert_eq!(vec![0, 1, 2], contract.get_user_posts("alice_near".to_string()).iter().map(|post| post.get_post_id()).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "Batch can contain at most 20 posts")]
+    fn create_posts_batch_too_large() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        let posts = (0..21).map(|i| (format!("Imported post {}", i), "Lets go Brandon!".to_string())).collect();
+        contract.create_posts_batch(posts);
+    }
+
+    #[test]
+    fn set_and_overwrite_profile() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        assert!(contract.get_profile("alice_near".to_string()).is_none());
+
+        contract.set_profile("Alice".to_string(), "Writes about NEAR".to_string(), "https://example.com/alice.png".to_string());
+        let profile = contract.get_profile("alice_near".to_string()).unwrap();
+        assert_eq!("Alice", profile.get_display_name());
+        assert_eq!("Writes about NEAR", profile.get_bio());
+        assert_eq!("https://example.com/alice.png", profile.get_avatar_url());
+
+        context.block_timestamp = 1_000;
+        set_caller(&mut context, "alice_near");
+        contract.set_profile("Alice B.".to_string(), "".to_string(), "".to_string());
+        let profile = contract.get_profile("alice_near".to_string()).unwrap();
+        assert_eq!("Alice B.", profile.get_display_name());
+        assert_eq!("", profile.get_bio());
+        assert_eq!(1_000, profile.get_updated_at());
+    }
+
+    #[test]
+    #[should_panic(expected = "Bio must be at most 500 characters long")]
+    fn profile_bio_too_long() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.set_profile("Alice".to_string(), "a".repeat(501), "".to_string());
+    }
+
+    #[test]
+    fn create_comment_returns_id() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(0, contract.create_comment(0, "This is the comment".to_string()));
+        assert_eq!(1, contract.reply(0, 0, "This is the reply".to_string()));
+        assert_eq!(Some(0), contract.get_comment(0, 1).unwrap().get_parent_comment_id());
+    }
+
+    #[test]
+    fn block_and_unblock_commenter() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.block_commenter("bob_near".to_string());
+        assert!(contract.is_blocked("alice_near".to_string(), "bob_near".to_string()));
+        assert!(!contract.is_blocked("alice_near".to_string(), "carol_near".to_string()));
+
+        contract.unblock_commenter("bob_near".to_string());
+        assert!(!contract.is_blocked("alice_near".to_string(), "bob_near".to_string()));
+
+        set_caller(&mut context, "bob_near");
+        contract.create_comment(0, "This is the comment".to_string());
+        assert_eq!(1, contract.get_post_total_comments(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "You are blocked from commenting on this author's posts")]
+    fn blocked_commenter_cannot_comment() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.block_commenter("bob_near".to_string());
+
+        set_caller(&mut context, "bob_near");
+        contract.create_comment(0, "This is the comment".to_string());
+    }
+
+    #[test]
+    fn test_post_cooldown() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        assert_eq!(60, contract.get_post_cooldown());
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.block_timestamp = 61_000_000_000;
+        set_caller(&mut context, "alice_near");
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        // the cooldown is per author
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is bob's title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        assert_eq!(3, contract.get_total_posts());
+    }
+
+    #[test]
+    #[should_panic(expected = "Please wait 30 seconds before creating another post")]
+    fn test_post_cooldown_rejects_rapid_posts() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.block_timestamp = 30_000_000_000;
+        set_caller(&mut context, "alice_near");
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    fn test_set_comments_enabled() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert!(contract.get_post(0).unwrap().are_comments_enabled());
+
+        contract.set_comments_enabled(0, false);
+        assert!(!contract.get_post(0).unwrap().are_comments_enabled());
+
+        contract.set_comments_enabled(0, true);
+        contract.create_comment(0, "This is the comment".to_string());
+        assert_eq!(1, contract.get_post_total_comments(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Comments are disabled for this post")]
+    fn test_comment_on_post_with_comments_disabled() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+        contract.set_comments_enabled(0, false);
+
+        contract.reply(0, 0, "This is the reply".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only author can enable or disable comments")]
+    fn test_set_comments_enabled_requires_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.set_comments_enabled(0, false);
+    }
+
+    #[test]
+    fn test_get_recent_comments() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        contract.create_comment(0, "First comment on post 0".to_string());
+        contract.create_comment(1, "First comment on post 1".to_string());
+        contract.create_comment(0, "Second comment on post 0".to_string());
+
+        let recent = contract.get_recent_comments(0, 10);
+        let ids: Vec<(usize, usize)> = recent.iter().map(|(post_id, comment)| (*post_id, comment.get_comment_id())).collect();
+        assert_eq!(vec![(0, 2), (1, 1), (0, 0)], ids);
+
+        let page = contract.get_recent_comments(1, 1);
+        assert_eq!(1, page.len());
+        assert_eq!(1, page[0].1.get_comment_id());
+
+        // deleted comments and comments of deleted posts drop out
+        contract.delete_comment(0, 2);
+        contract.delete_post(1, None);
+        let recent = contract.get_recent_comments(0, 10);
+        assert_eq!(1, recent.len());
+        assert_eq!(0, recent[0].1.get_comment_id());
+    }
+
+    #[test]
+    fn test_get_comments_by_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
+        contract.create_comment(0, "Bob's comment on post 0".to_string());
+        contract.create_comment(1, "Bob's comment on post 1".to_string());
+        set_caller(&mut context, "carol_near");
+        contract.create_comment(1, "Carol's comment on post 1".to_string());
+
+        let comments = contract.get_comments_by_author("bob_near".to_string(), 0, 10);
+        let ids: Vec<(usize, usize)> = comments.iter().map(|(post_id, comment)| (*post_id, comment.get_comment_id())).collect();
+        assert_eq!(vec![(0, 0), (1, 1)], ids);
+
+        let page = contract.get_comments_by_author("bob_near".to_string(), 1, 1);
+        assert_eq!(1, page.len());
+        assert_eq!(1, page[0].1.get_comment_id());
+
+        // deleted comments drop out of the index
+        set_caller(&mut context, "bob_near");
+        contract.delete_my_comment(0, 0);
+        let comments = contract.get_comments_by_author("bob_near".to_string(), 0, 10);
+        assert_eq!(1, comments.len());
+        assert_eq!(1, comments[0].1.get_comment_id());
+        assert!(contract.get_comments_by_author("dave_near".to_string(), 0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_mention_notifies_account() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "carol_near");
+        contract.create_comment(0, "@bob.near check this, and @bob.near again!".to_string());
+
+        let notifications = contract.get_notifications("bob.near".to_string());
+        assert_eq!(1, notifications.len());
+        assert_eq!(0, notifications[0].get_post_id());
+        assert_eq!(0, notifications[0].get_comment_id());
+        assert_eq!("carol_near", notifications[0].get_from());
+
+        set_caller(&mut context, "bob.near");
+        contract.clear_notifications();
+        assert!(contract.get_notifications("bob.near".to_string()).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_mention_is_ignored() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "carol_near");
+        contract.create_comment(0, "Hey @Bob#Near and @ and email@bob.near".to_string());
+
+        assert!(contract.get_notifications("Bob#Near".to_string()).is_empty());
+        assert!(contract.get_notifications("bob.near".to_string()).is_empty());
+        assert!(contract.get_notifications("bob".to_string()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Post has donations, soft delete it instead")]
+    fn test_delete_post_with_donations() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = DEFAULT_MIN_DONATION;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Donation".to_string());
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        contract.delete_post(0, None);
+    }
+
+    #[test]
+    fn test_force_delete_post_with_donations() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = DEFAULT_MIN_DONATION;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Donation".to_string());
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        contract.delete_post(0, Some(true));
+        assert!(contract.get_post(0).is_none());
+    }
+
+    #[test]
+    fn test_get_post_page() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        for i in 0..5 {
+            contract.create_comment(0, format!("This is comment {}", i));
+        }
+
+        let page = contract.get_post_page(0, 1, 2);
+        assert_eq!("This is the title", page.post.get_title());
+        assert!(page.post.get_comments().is_empty());
+        assert_eq!(5, page.comment_count);
+        let ids: Vec<usize> = page.comments.iter().map(|comment| comment.get_comment_id()).collect();
+        assert_eq!(vec![1, 2], ids);
+
+        let page = contract.get_post_page(0, 4, 10);
+        assert_eq!(1, page.comments.len());
+        assert_eq!(5, page.comment_count);
+    }
+
+    #[test]
+    fn test_allowlist_allows_listed_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_allowlist_enabled(true);
+        contract.add_author("bob_near".to_string());
+
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(1, contract.get_total_posts());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only allowed authors can create posts")]
+    fn test_allowlist_denies_unlisted_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_allowlist_enabled(true);
+        contract.add_author("bob_near".to_string());
+        contract.remove_author("bob_near".to_string());
+
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only allowed authors can create posts")]
+    fn test_allowlist_applies_to_nonce_posts() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_allowlist_enabled(true);
+
+        set_caller(&mut context, "bob_near");
+        contract.create_post_with_nonce("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None, "nonce-1".to_string());
+    }
+
+    #[test]
+    fn test_allowlist_disabled() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        assert!(!contract.is_allowlist_enabled());
+
+        set_caller(&mut context, "bob_near");
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(1, contract.get_total_posts());
+        assert!(!contract.is_allowed_author("bob_near".to_string()));
+    }
+
+    #[test]
+    fn test_get_posts_since() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        for i in 0..5 {
+            contract.create_post(format!("This is title {}", i), "Lets go Brandon!".to_string(), None, None, None, None);
+        }
+
+        let ids: Vec<usize> = contract.get_posts_since(2, 10).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![3, 4], ids);
+
+        contract.delete_post(3, None);
+        let ids: Vec<usize> = contract.get_posts_since(2, 10).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![4], ids);
+        assert_eq!(1, contract.get_posts_since(0, 1).len());
+        assert!(contract.get_posts_since(4, 10).is_empty());
+    }
+
+    #[test]
+    fn test_donate_to_comment() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        set_caller(&mut context, "bob_near");
+        contract.create_comment(0, "This is the comment".to_string());
+
+        context.attached_deposit = DEFAULT_MIN_DONATION;
+        set_caller(&mut context, "carol_near");
+        contract.donate_to_comment(0, 0, "Great comment".to_string());
+
+        let donation_logs = contract.get_comment_donations(0, 0);
+        assert_eq!(1, donation_logs.len());
+        assert_eq!(DEFAULT_MIN_DONATION, donation_logs[0].get_amount());
+        assert_eq!("carol_near", donation_logs[0].get_donor());
+        assert_eq!(0, contract.get_post(0).unwrap().get_donation_count());
+
+        // the tip pays for its log, bob gets the rest
+        let storage_cost = contract.storage_balance_of("carol_near".to_string()).0;
+        assert!(storage_cost > 0);
+        assert_eq!(DEFAULT_MIN_DONATION - storage_cost, donation_logs[0].get_net_amount());
+        assert_eq!(U128(DEFAULT_MIN_DONATION - storage_cost), contract.get_pending_donations("bob_near".to_string()));
+        assert_eq!(U128(0), contract.get_pending_donations("alice_near".to_string()));
+
+        // counted and indexed like post donations
+        assert_eq!(1, contract.get_total_donations());
+        assert_eq!("Great comment", contract.get_donation(0).unwrap().get_message());
+    }
+
+    #[test]
+    fn test_deleted_comment_drops_its_tips() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        set_caller(&mut context, "bob_near");
+        contract.create_comment(0, "This is the comment".to_string());
+
+        context.attached_deposit = DEFAULT_MIN_DONATION;
+        set_caller(&mut context, "carol_near");
+        contract.donate_to_comment(0, 0, "Great comment".to_string());
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "bob_near");
+        contract.delete_my_comment(0, 0);
+
+        assert!(contract.get_comment_donations(0, 0).is_empty());
+        assert!(contract.get_donation(0).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Please wait")]
+    fn test_donate_to_comment_cooldown() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        set_caller(&mut context, "bob_near");
+        contract.create_comment(0, "This is the comment".to_string());
+
+        context.attached_deposit = DEFAULT_MIN_DONATION;
+        set_caller(&mut context, "carol_near");
+        contract.donate_to_comment(0, 0, "Great comment".to_string());
+        set_caller(&mut context, "carol_near");
+        contract.donate_to_comment(0, 0, "Great comment".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Comment does not exist")]
+    fn test_donate_to_comment_of_other_post() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        context.attached_deposit = DEFAULT_MIN_DONATION;
+        set_caller(&mut context, "carol_near");
+        contract.donate_to_comment(1, 0, "Great comment".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Content rejected")]
+    fn test_banned_word_rejects_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.add_banned_word(" Spam ".to_string());
+        assert_eq!(vec!["spam".to_string()], contract.get_banned_words());
+
+        contract.create_post("This is the title".to_string(), "Buy cheap SPAM here!".to_string(), None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Content rejected")]
+    fn test_banned_word_rejects_comment() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.add_banned_word("spam".to_string());
+        contract.create_comment(0, "This comment is Spam".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Content rejected")]
+    fn test_banned_word_rejects_nonce_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.add_banned_word("spam".to_string());
+
+        contract.create_post_with_nonce("This is the title".to_string(), "Buy cheap spam here!".to_string(), None, None, None, None, "nonce-1".to_string());
+    }
+
+    #[test]
+    fn test_clean_post_passes_blocklist() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+        contract.add_banned_word("spam".to_string());
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(1, contract.get_total_posts());
+
+        contract.remove_banned_word("SPAM".to_string());
+        assert!(contract.get_banned_words().is_empty());
+        contract.create_post("This is the second title".to_string(), "Spam is fine now".to_string(), None, None, None, None);
+        assert_eq!(2, contract.get_total_posts());
+    }
+
+    #[test]
+    fn test_total_comments_and_donations() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+        contract.set_donation_cooldown(0);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+        contract.create_comment(1, "This is the comment".to_string());
+        contract.create_comment(1, "This is another comment".to_string());
+
+        context.attached_deposit = DEFAULT_MIN_DONATION;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Donation".to_string());
+        contract.donate(0, "Donation".to_string());
+        contract.donate(1, "Donation".to_string());
+
+        assert_eq!(3, contract.get_total_comments());
+        assert_eq!(3, contract.get_total_donations());
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        contract.delete_comment(1, 2);
+        assert_eq!(2, contract.get_total_comments());
+        assert_eq!(3, contract.get_total_donations());
+    }
+
+    #[test]
+    fn test_schedule_post_in_future() {
+        let mut context = get_context(vec![], false);
+        context.block_timestamp = 100;
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.schedule_post("This is the title".to_string(), "Lets go Brandon!".to_string(), 200);
+        assert!(contract.get_all_posts(0, 10, None, None).is_empty());
+        assert_eq!(0, contract.publish_due());
+
+        context.block_timestamp = 200;
+        context.storage_usage = env::storage_usage();
+        testing_env!(context);
+        assert_eq!(1, contract.get_all_posts(0, 10, None, None).len());
+
+        assert_eq!(1, contract.publish_due());
+        assert_eq!(0, contract.publish_due());
+        let post = contract.get_post(0).unwrap();
+        assert!(post.is_published());
+        assert_eq!(None, post.get_publish_at());
+    }
+
+    #[test]
+    fn test_schedule_post_in_past() {
+        let mut context = get_context(vec![], false);
+        context.block_timestamp = 100;
+        testing_env!(context);
+        let mut contract = Blog::default();
 
-    // mock the context for testing, notice "signer_account_id" that was accessed above from env::
-    fn get_context(input: Vec<u8>, is_view: bool) -> VMContext {
-        VMContext {
-            current_account_id: "decentrablog.npmrunstart.testnet".to_string(),
-            signer_account_id: "alice_near".to_string(),
-            signer_account_pk: vec![0, 1, 2],
-            predecessor_account_id: "alice_near".to_string(),
-            input,
-            block_index: 0,
-            block_timestamp: 0,
-            account_balance: 1000000000000000000000000,
-            account_locked_balance: 0,
-            storage_usage: 0,
-            attached_deposit: 0,
-            prepaid_gas: 10u64.pow(18),
-            random_seed: vec![0, 1, 2],
-            is_view,
-            output_data_receivers: vec![],
-            epoch_height: 19,
+        contract.schedule_post("This is the title".to_string(), "Lets go Brandon!".to_string(), 50);
+        assert_eq!(1, contract.get_all_posts(0, 10, None, None).len());
+        assert!(contract.get_post(0).unwrap().is_published());
+    }
+
+    #[test]
+    fn test_get_random_post() {
+        let mut context = get_context(vec![], false);
+        context.random_seed = vec![7];
+        testing_env!(context);
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+        assert!(contract.get_random_post().is_none());
+
+        for i in 0..3 {
+            contract.create_post(format!("This is title {}", i), "Lets go Brandon!".to_string(), None, None, None, None);
         }
+
+        // 7 % 3 picks the second post
+        assert_eq!(1, contract.get_random_post().unwrap().get_post_id());
+        assert_eq!(1, contract.get_random_post().unwrap().get_post_id());
+
+        contract.soft_delete_post(1);
+        assert_eq!(2, contract.get_random_post().unwrap().get_post_id());
     }
 
     #[test]
-    fn create_post() {
+    fn test_pin_comment() {
         let context = get_context(vec![], false);
         testing_env!(context);
         let mut contract = Blog::default();
-        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string());
 
-        //log id
-        env::log(format!("Debug here {}", contract.get_post(0).unwrap().get_post_id()).as_bytes());
-        
-        assert_eq!(
-            "This is the title".to_string(),
-            contract.get_post(0).unwrap().get_title()
-        );
-        assert_eq!(
-            "Lets go Brandon!".to_string(),
-            contract.get_post(0).unwrap().get_body()
-        );
-        assert_eq!(1, contract.get_total_posts());
-        assert_eq!(0, contract.get_post(0).unwrap().get_post_id());
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        for i in 0..3 {
+            contract.create_comment(0, format!("This is comment {}", i));
+        }
 
-        //test get_user_posts
-        let user_posts = contract.get_user_posts("alice_near".to_string());
-        assert_eq!(1, user_posts.len());
-        assert_eq!(0, user_posts[0].get_post_id());
+        contract.pin_comment(0, 2);
+        assert_eq!(Some(2), contract.get_post(0).unwrap().get_pinned_comment_id());
+
+        let ids: Vec<usize> = contract.get_comments(0).iter().map(|comment| comment.get_comment_id()).collect();
+        assert_eq!(vec![2, 0, 1], ids);
+        let ids: Vec<usize> = contract.get_comments_paginated(0, 0, 2).iter().map(|comment| comment.get_comment_id()).collect();
+        assert_eq!(vec![2, 0], ids);
+        let ids: Vec<usize> = contract.get_comments_paginated(0, 2, 2).iter().map(|comment| comment.get_comment_id()).collect();
+        assert_eq!(vec![1], ids);
+
+        contract.unpin_comment(0);
+        assert_eq!(None, contract.get_post(0).unwrap().get_pinned_comment_id());
+        let ids: Vec<usize> = contract.get_comments(0).iter().map(|comment| comment.get_comment_id()).collect();
+        assert_eq!(vec![0, 1, 2], ids);
     }
 
     #[test]
-    fn delete_a_post_then_add_two_posts() {
+    fn test_deleting_pinned_comment_unpins_it() {
         let context = get_context(vec![], false);
         testing_env!(context);
         let mut contract = Blog::default();
-        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string());
-        contract.delete_post(0);
-        
-        assert_eq!(0, contract.get_total_posts(), "Total posts should be 0");
 
-        // add a post
-        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string());
-        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string());
-        assert_eq!(2, contract.get_total_posts());
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+        contract.pin_comment(0, 0);
 
-        //next post id
-        assert_eq!(3, contract.get_next_post_id());
+        contract.delete_comment(0, 0);
+        assert_eq!(None, contract.get_post(0).unwrap().get_pinned_comment_id());
     }
 
     #[test]
-    fn return_owner_account_id() {
+    #[should_panic(expected = "Only author can pin comments")]
+    fn test_pin_comment_requires_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_comment(0, "This is the comment".to_string());
+
+        set_caller(&mut context, "bob_near");
+        contract.pin_comment(0, 0);
+    }
+
+    #[test]
+    fn test_post_exists() {
         let context = get_context(vec![], false);
         testing_env!(context);
-        let contract = Blog::default();
-        assert_eq!(
-            "alice_near".to_string(),
-            contract.get_owner()
-        );
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        assert!(contract.post_exists(0));
+        assert!(!contract.post_exists(1));
+
+        contract.delete_post(0, None);
+        assert!(!contract.post_exists(0));
     }
 
     #[test]
-    fn create_comment() {
+    fn test_comment_exists() {
         let context = get_context(vec![], false);
         testing_env!(context);
         let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
 
-        // Create the first post
-        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string());
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
         contract.create_comment(0, "This is the comment".to_string());
 
-        assert_eq!(
-            "This is the comment".to_string(),
-            contract.get_comment(0).get_body()
-        );
-        assert_eq!(0, contract.get_comment(0).get_comment_id());
+        assert!(contract.comment_exists(0, 0));
+        assert!(!contract.comment_exists(0, 1));
+        assert!(!contract.comment_exists(1, 0));
+        assert!(!contract.comment_exists(2, 0));
+    }
 
-        contract.create_comment(0, "This is comment 2, id 1".to_string());
-        contract.create_comment(0, "This is comment 3, id 2".to_string());
+    #[test]
+    #[should_panic(expected = "Post does not exist")]
+    fn test_get_comments_of_missing_post() {
+        let context = get_context(vec![], false);
+        testing_env!(context);
+        let contract = Blog::default();
 
-        // Check if the comments is there
-        assert_eq!(
-            "This is comment 2, id 1".to_string(),
-            contract.get_comment(1).get_body()
-        );
-        assert_eq!(
-            "This is comment 3, id 2".to_string(),
-            contract.get_comment(2).get_body()
-        );
+        contract.get_comments(0);
+    }
 
-        let comments = contract.get_post(0).unwrap().get_comments();
+    #[test]
+    fn test_get_donation_by_id() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+        contract.set_min_donation(U128(1));
 
-        //assert size of comments
-        assert_eq!(3, comments.len(), "Comments size is not 3");
-        assert_eq!(3, contract.get_post_total_comments(0), "get_post_total_comments is not 3");
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
 
-        //Check comment string
-        assert_eq!(
-            "This is comment 2, id 1".to_string(),
-            contract.get_comment(1).get_body()
-        );
-        assert_eq!(
-            "This is comment 3, id 2".to_string(),
-            contract.get_comment(2).get_body()
-        );
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(1, "To the second post".to_string());
+        context.attached_deposit = 2000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "To the first post".to_string());
+
+        let donation_log = contract.get_donation(0).unwrap();
+        assert_eq!(1, donation_log.get_post_id());
+        assert_eq!(1000, donation_log.get_amount());
+
+        let donation_log = contract.get_donation(1).unwrap();
+        assert_eq!(0, donation_log.get_post_id());
+        assert_eq!(2000, donation_log.get_amount());
+
+        assert!(contract.get_donation(2).is_none());
     }
 
     #[test]
-    fn upvote_test() {
+    fn test_hide_threshold() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+        assert_eq!(DEFAULT_HIDE_THRESHOLD, contract.get_hide_threshold());
+        contract.set_hide_threshold(-2);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        // post 0 at -2, post 1 just above at -1
+        set_caller(&mut context, "bob_near");
+        contract.downvote(0);
+        contract.downvote(1);
+        set_caller(&mut context, "carol_near");
+        contract.downvote(0);
+
+        let ids: Vec<usize> = contract.get_all_posts(0, 10, None, None).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![1], ids);
+        let ids: Vec<usize> = contract.get_trending(10, None).iter().map(|post| post.get_post_id()).collect();
+        assert_eq!(vec![1], ids);
+
+        assert_eq!(2, contract.get_all_posts(0, 10, None, Some(true)).len());
+        assert_eq!(2, contract.get_trending(10, Some(true)).len());
+        assert!(contract.get_post(0).is_some());
+    }
+
+    #[test]
+    fn test_get_tag_counts() {
         let context = get_context(vec![], false);
         testing_env!(context);
         let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
 
-        // Create the first post
-        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string());
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), Some(vec!["rust".to_string(), "near".to_string()]), None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), Some(vec!["rust".to_string()]), None, None, None);
+        contract.create_post("This is the third title".to_string(), "Lets go Brandon!".to_string(), Some(vec!["rust".to_string(), "web".to_string(), "near".to_string()]), None, None, None);
 
-        // Upvote the post
+        let counts = contract.get_tag_counts(0, 10);
+        assert_eq!(vec![("rust".to_string(), 3), ("near".to_string(), 2), ("web".to_string(), 1)], counts);
+        assert_eq!(vec![("near".to_string(), 2)], contract.get_tag_counts(1, 1));
+
+        let mut tags = contract.get_all_tags();
+        tags.sort();
+        assert_eq!(vec!["near".to_string(), "rust".to_string(), "web".to_string()], tags);
+
+        // a tag without posts leaves the cloud
+        contract.delete_post(2, None);
+        assert_eq!(vec![("rust".to_string(), 2), ("near".to_string(), 1)], contract.get_tag_counts(0, 10));
+        assert_eq!(2, contract.get_all_tags().len());
+    }
+
+    #[test]
+    fn test_get_author_summary() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_post_cooldown(0);
+        contract.set_min_donation(U128(1));
+        contract.set_donation_fee(1000);
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.create_post("This is the second title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        set_caller(&mut context, "bob_near");
         contract.upvote(0);
+        contract.downvote(1);
+        contract.create_comment(0, "This is the comment".to_string());
+        contract.create_comment(0, "This is another comment".to_string());
+        contract.create_comment(1, "This is the comment".to_string());
+        set_caller(&mut context, "carol_near");
+        contract.upvote(0);
+        contract.create_post("This is carol's title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
 
-        // Check if the upvote is there
-        assert_eq!(
-            1,
-            contract.get_post(0).unwrap().get_upvotes().len()
-        );
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Donation".to_string());
+        context.attached_deposit = 500;
+        set_caller(&mut context, "bob_near");
+        contract.donate(1, "Donation".to_string());
 
-        // upvote 10 times 
-        for _ in 0..10 {
-            contract.upvote(0);
-        }
+        let summary = contract.get_author_summary("alice_near".to_string());
+        assert_eq!(2, summary.post_count);
+        assert_eq!(2, summary.total_upvotes);
+        assert_eq!(1, summary.total_downvotes);
+        assert_eq!(3, summary.total_comments_received);
+        // net of the 10% fee, the same as the author was credited
+        assert_eq!(U128(1350), summary.total_donated_received);
+        assert_eq!(contract.get_pending_donations("alice_near".to_string()), summary.total_donated_received);
 
-        // downvote 5 times
-        for _ in 0..5 {
-            contract.downvote(0);
-        }
+        let summary = contract.get_author_summary("dave_near".to_string());
+        assert_eq!(0, summary.post_count);
+        assert_eq!(U128(0), summary.total_donated_received);
+    }
 
-        // check statistic, 10 times of upvote is 1 in an account
-        // 5 times of downvote is the same
-        assert_eq!(
-            (0, 1),
-            contract.get_votes_statistics(0)
-        );
-        
+    #[test]
+    fn test_min_donation() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        assert_eq!(DEFAULT_MIN_DONATION, contract.get_min_donation().0);
+
+        contract.set_min_donation(U128(1000));
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Just enough".to_string());
+        assert_eq!(1000, contract.get_post(0).unwrap().get_total_donation());
     }
 
     #[test]
-    fn test_paging_post() {
-        let context = get_context(vec![], false);
-        testing_env!(context);
+    #[should_panic(expected = "Donation must be at least 1000 yoctoNEAR")]
+    fn test_donation_below_minimum() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
         let mut contract = Blog::default();
 
-        // Loop 100 post and create them
-        for i in 0..45 {
-            contract.create_post(format!("This is the title {}", i), format!("Lets go Brandon! {}", i));
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.set_min_donation(U128(1000));
+
+        context.attached_deposit = 999;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Dust".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set the minimum donation")]
+    fn test_set_min_donation_requires_owner() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        set_caller(&mut context, "bob_near");
+        contract.set_min_donation(U128(1));
+    }
+
+    #[test]
+    fn test_donation_credits_author() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_donation_cooldown(0);
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "First donation".to_string());
+        contract.donate(0, "Second donation".to_string());
+
+        assert_eq!(2000000, contract.get_pending_donations("alice_near".to_string()).0);
+        assert_eq!(0, contract.get_pending_donations("bob_near".to_string()).0);
+    }
+
+    #[test]
+    fn test_withdraw_donations() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "First donation".to_string());
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        contract.withdraw_donations();
+
+        assert_eq!(0, contract.get_pending_donations("alice_near".to_string()).0);
+    }
+
+    #[test]
+    fn test_failed_withdraw_is_credited_back() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        // the callback runs as the contract itself, with a failed transfer
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context, VMConfig::default(), RuntimeFeesConfig::default(), Default::default(), vec![PromiseResult::Failed]);
+
+        assert!(!contract.on_withdraw_complete("alice_near".to_string(), U128(1000000)));
+        assert_eq!(1000000, contract.get_pending_donations("alice_near".to_string()).0);
+    }
+
+    // bob's donation to post 0, checked against nft_near
+    fn holder_donation() -> HolderDonation {
+        HolderDonation {
+            post_id: 0,
+            donor: "bob_near".to_string(),
+            amount: U128(1000),
+            message: "For holders".to_string(),
+            nft_contract: "nft_near".to_string(),
         }
+    }
 
-        assert_eq!(45, contract.get_total_posts(), "Total post is not 45");
-        
-        // Check paging post call
-        // let posts = contract.get_paging_posts(1, 10);
-        // assert_eq!(10, posts.len(), "Paging post size is not 10");
-        // assert_eq!(0, posts[0].get_post_id(), "Paging post id is not 10");
-        // assert_eq!(9, posts[9].get_post_id(), "Paging post id is not 20");
+    #[test]
+    fn test_holder_donation_is_recorded() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
 
-        // Check paging post call
-        let posts = contract.get_paging_posts(5, 10);
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.set_holder_contract(Some("nft_near".to_string()));
 
-        assert_eq!(5, posts.len(), "Paging post size is not 5");
-        assert_eq!(40, posts[0].get_post_id(), "Paging post id is not 40");
-        assert_eq!(44, posts[4].get_post_id(), "Paging post id is not 44");
+        // the callback runs as the contract itself, with the token owned by the donor
+        let token = json!({ "token_id": "1", "owner_id": "bob_near", "metadata": null });
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context, VMConfig::default(), RuntimeFeesConfig::default(), Default::default(), vec![PromiseResult::Successful(token.to_string().into_bytes())]);
+
+        assert!(contract.on_holder_verified(holder_donation()));
+        let donation_log = &contract.get_donations(0, 0, 1)[0];
+        assert!(donation_log.is_holder());
+        assert_eq!(Some("nft_near".to_string()), donation_log.get_holder_contract());
+        assert_eq!("bob_near", donation_log.get_donor());
+        assert_eq!(1000, donation_log.get_amount());
+        assert_eq!(U128(1000), contract.get_pending_donations("alice_near".to_string()));
     }
 
     #[test]
-    fn test_donation() {
+    fn test_holder_donation_within_cooldown_is_refunded() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.set_holder_contract(Some("nft_near".to_string()));
+
+        // bob donated while the holder check was running
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Donation".to_string());
+
+        let token = json!({ "token_id": "1", "owner_id": "bob_near" });
+        context.attached_deposit = 0;
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context, VMConfig::default(), RuntimeFeesConfig::default(), Default::default(), vec![PromiseResult::Successful(token.to_string().into_bytes())]);
+
+        assert!(!contract.on_holder_verified(holder_donation()));
+        assert_eq!(1, contract.get_donation_count(0));
+        assert_eq!(U128(1000), contract.get_pending_donations("alice_near".to_string()));
+    }
+
+    #[test]
+    fn test_holder_donation_below_raised_minimum_is_refunded() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.set_holder_contract(Some("nft_near".to_string()));
+        contract.set_min_donation(U128(2000));
+
+        let token = json!({ "token_id": "1", "owner_id": "bob_near" });
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context, VMConfig::default(), RuntimeFeesConfig::default(), Default::default(), vec![PromiseResult::Successful(token.to_string().into_bytes())]);
+
+        assert!(!contract.on_holder_verified(holder_donation()));
+        assert_eq!(0, contract.get_donation_count(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Please wait 10 seconds before donating to this post again")]
+    fn test_holder_donation_checks_cooldown_upfront() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.set_holder_contract(Some("nft_near".to_string()));
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate(0, "Donation".to_string());
+        contract.donate_as_holder(0, "1".to_string(), "For holders".to_string());
+    }
+
+    #[test]
+    fn test_non_holder_donation_is_refunded() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.set_holder_contract(Some("nft_near".to_string()));
+
+        let token = json!({ "token_id": "1", "owner_id": "carol_near" });
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context, VMConfig::default(), RuntimeFeesConfig::default(), Default::default(), vec![PromiseResult::Successful(token.to_string().into_bytes())]);
+
+        assert!(!contract.on_holder_verified(holder_donation()));
+        assert_eq!(0, contract.get_donation_count(0));
+        assert_eq!(U128(0), contract.get_pending_donations("alice_near".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "The author has not set an NFT contract")]
+    fn test_holder_donation_needs_author_contract() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 1000;
+        set_caller(&mut context, "bob_near");
+        contract.donate_as_holder(0, "1".to_string(), "For holders".to_string());
+    }
+
+    #[test]
+    fn test_holder_donation_after_contract_change_is_refunded() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+        contract.set_min_donation(U128(1));
+
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+        contract.set_holder_contract(Some("other_nft_near".to_string()));
+
+        // checked against nft_near, which the author no longer accepts
+        let token = json!({ "token_id": "1", "owner_id": "bob_near" });
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context, VMConfig::default(), RuntimeFeesConfig::default(), Default::default(), vec![PromiseResult::Successful(token.to_string().into_bytes())]);
+
+        assert!(!contract.on_holder_verified(holder_donation()));
+        assert_eq!(0, contract.get_donation_count(0));
+    }
+
+    #[test]
+    fn test_successful_withdraw_is_not_credited_back() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
+
+        context.predecessor_account_id = context.current_account_id.clone();
+        context.storage_usage = env::storage_usage();
+        testing_env!(context, VMConfig::default(), RuntimeFeesConfig::default(), Default::default(), vec![PromiseResult::Successful(vec![])]);
+
+        assert!(contract.on_withdraw_complete("alice_near".to_string(), U128(1000000)));
+        assert_eq!(0, contract.get_pending_donations("alice_near".to_string()).0);
+    }
+
+    #[test]
+    #[should_panic(expected = "No donations to withdraw")]
+    fn test_withdraw_without_donations() {
         let context = get_context(vec![], false);
         testing_env!(context);
         let mut contract = Blog::default();
 
-        // Create the first post
-        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string());
+        contract.withdraw_donations();
+    }
 
-        // Donate
-        contract.donate(0, 1000000, "Support Trump for the USA".to_string());
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than 0")]
+    fn test_donation_without_deposit() {
+        let mut context = get_context(vec![], false);
+        testing_env!(context.clone());
+        let mut contract = Blog::default();
 
-        // Check if the donation is there
-        assert_eq!(
-            1,
-            contract.get_post(0).unwrap().get_total_donation()
-        );
+        contract.create_post("This is the title".to_string(), "Lets go Brandon!".to_string(), None, None, None, None);
+
+        context.attached_deposit = 0;
+        set_caller(&mut context, "alice_near");
+        contract.donate(0, "Support Trump for the USA".to_string());
     }
 }
\ No newline at end of file
@@ -0,0 +1,57 @@
+use std::collections::HashSet;
+
+use near_sdk::AccountId;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::UnorderedMap;
+
+use crate::{CommentId, PostId};
+
+/// `Blog` as it was first deployed, before moderators, tags and the donation ledger.
+/// `Blog::migrate` reads this layout and rewrites it into the current one.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldBlog {
+    pub owner: AccountId,
+    pub user_posts: UnorderedMap<AccountId, Vec<usize>>,
+    pub posts: UnorderedMap<PostId, OldPost>,
+    pub comments: UnorderedMap<CommentId, OldComment>,
+
+    pub next_post_id: usize,
+    pub next_comment_id: usize,
+    pub next_donation_id: usize,
+}
+
+/// `Post` as it was first deployed.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldPost {
+    pub post_id: usize,
+    pub title: String,
+    pub body: String,
+    pub author: AccountId,
+    pub created_at: u64,
+    pub comments: Vec<usize>,
+
+    pub upvotes: HashSet<AccountId>,
+    pub downvotes: HashSet<AccountId>,
+
+    pub donation_logs: Vec<OldDonationLog>,
+}
+
+/// `DonationLog` as it was first deployed, before token donations.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldDonationLog {
+    pub donation_id: usize,
+    pub amount: u128,
+    pub donor: AccountId,
+    pub created_at: u64,
+    pub message: String,
+    pub post_id: usize,
+}
+
+/// `Comment` as it was first deployed.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldComment {
+    pub comment_id: usize,
+    pub body: String,
+    pub author: AccountId,
+    pub created_at: u64,
+}
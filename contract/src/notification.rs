@@ -0,0 +1,43 @@
+use near_sdk::AccountId;
+use near_sdk::serde::{Serialize, Deserialize};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+
+use crate::{CommentId, PostId};
+
+/// Tells an account it was mentioned in a comment.
+#[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Notification {
+    post_id: PostId,
+    comment_id: CommentId,
+    // the commenter
+    from: AccountId,
+    created_at: u64,
+}
+
+impl Notification {
+    pub fn new(post_id: PostId, comment_id: CommentId, from: AccountId, created_at: u64) -> Self {
+        Self {
+            post_id,
+            comment_id,
+            from,
+            created_at,
+        }
+    }
+
+    pub fn get_post_id(&self) -> PostId {
+        self.post_id
+    }
+
+    pub fn get_comment_id(&self) -> CommentId {
+        self.comment_id
+    }
+
+    pub fn get_from(&self) -> AccountId {
+        self.from.clone()
+    }
+
+    pub fn get_created_at(&self) -> u64 {
+        self.created_at
+    }
+}
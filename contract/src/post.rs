@@ -1,10 +1,44 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use near_sdk::{serde::{Serialize, Deserialize}, AccountId};
+use near_sdk::{env, serde::{Serialize, Deserialize}, AccountId};
+use near_sdk::json_types::U128;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 
 use crate::{PostId, donation::DonationLog};
 
+// older revisions are dropped past this
+const MAX_REVISIONS: usize = 10;
+
+// characters of the body used when the author doesn't write an excerpt
+const EXCERPT_LENGTH: usize = 160;
+
+fn derive_excerpt(body: &str) -> String {
+    body.chars().take(EXCERPT_LENGTH).collect::<String>().trim().to_string()
+}
+
+/// A version of the post that was replaced by an edit at `edited_at`.
+#[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PostRevision {
+    title: String,
+    body: String,
+    edited_at: u64,
+}
+
+impl PostRevision {
+    pub fn get_title(&self) -> String {
+        self.title.clone()
+    }
+
+    pub fn get_body(&self) -> String {
+        self.body.clone()
+    }
+
+    pub fn get_edited_at(&self) -> u64 {
+        self.edited_at
+    }
+}
+
 /// Implements both `serde` and `borsh` serialization.
 /// `serde` is typically useful when returning a struct in JSON format for a frontend.
 #[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
@@ -12,34 +46,176 @@ use crate::{PostId, donation::DonationLog};
 pub struct Post {
     post_id: usize,
     title: String,
+    slug: String,
     body: String,
+    // shown in feeds instead of the full body
+    excerpt: String,
+    // false while the excerpt is derived from the body
+    excerpt_explicit: bool,
+    cover_image_url: String,
     author: AccountId,
     created_at: u64,
+    // new field, state written before it was added has to be migrated
+    updated_at: u64,
     comments: Vec<usize>,
+    tags: Vec<String>,
+    // oldest first
+    revisions: Vec<PostRevision>,
+    // drafts are only listed to their author
+    published: bool,
+    // a scheduled draft counts as published from this timestamp on
+    publish_at: Option<u64>,
+    comments_enabled: bool,
+    // listed before the other comments
+    pinned_comment_id: Option<usize>,
+    // soft-deleted posts keep their record but are hidden from feeds
+    deleted: bool,
+    nsfw: bool,
 
     upvotes: HashSet<AccountId>,
     downvotes: HashSet<AccountId>,
+    // one reaction per account
+    reactions: HashMap<AccountId, String>,
     
     donation_logs: Vec<DonationLog>,
+    total_donated: U128,
+    views: u64,
 }
 
 impl Post {
-    pub fn new(post_id: usize, title: String, body: String, author: AccountId, created_at: u64) -> Self {
+    pub fn new(post_id: usize, title: String, body: String, author: AccountId, created_at: u64, tags: Vec<String>, published: bool) -> Self {
         Self {
             post_id,
             title,
+            slug: String::new(),
+            excerpt: derive_excerpt(&body),
+            excerpt_explicit: false,
+            cover_image_url: String::new(),
             body,
             author,
             created_at,
+            updated_at: created_at,
             comments: Vec::new(),
+            tags,
+            revisions: Vec::new(),
+            published,
+            publish_at: None,
+            comments_enabled: true,
+            pinned_comment_id: None,
+            deleted: false,
+            nsfw: false,
 
             upvotes: HashSet::new(),
             downvotes: HashSet::new(),
+            reactions: HashMap::new(),
 
             donation_logs: Vec::new(),
+            total_donated: U128(0),
+            views: 0,
         }
     }
     
+    pub fn edit(&mut self, title: String, body: String, updated_at: u64) {
+        if self.revisions.len() == MAX_REVISIONS {
+            self.revisions.remove(0);
+        }
+        self.revisions.push(PostRevision {
+            title: std::mem::replace(&mut self.title, title),
+            body: std::mem::replace(&mut self.body, body),
+            edited_at: updated_at,
+        });
+        if !self.excerpt_explicit {
+            self.excerpt = derive_excerpt(&self.body);
+        }
+        self.updated_at = updated_at;
+    }
+
+    pub fn get_revisions(&self) -> Vec<PostRevision> {
+        self.revisions.clone()
+    }
+
+    pub fn publish(&mut self) {
+        self.published = true;
+        self.publish_at = None;
+    }
+
+    pub fn unpublish(&mut self) {
+        self.published = false;
+        self.publish_at = None;
+    }
+
+    pub fn is_published(&self) -> bool {
+        self.published || self.is_due()
+    }
+
+    pub fn set_publish_at(&mut self, publish_at: u64) {
+        self.publish_at = Some(publish_at);
+    }
+
+    pub fn get_publish_at(&self) -> Option<u64> {
+        self.publish_at
+    }
+
+    // scheduled and past its publish time
+    pub fn is_due(&self) -> bool {
+        self.publish_at.is_some_and(|publish_at| env::block_timestamp() >= publish_at)
+    }
+
+    pub fn soft_delete(&mut self) {
+        self.deleted = true;
+    }
+
+    pub fn restore(&mut self) {
+        self.deleted = false;
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deleted
+    }
+
+    // whether the post shows up in feeds and search
+    pub fn is_listed(&self) -> bool {
+        self.is_published() && !self.deleted
+    }
+
+    pub fn set_nsfw(&mut self, nsfw: bool) {
+        self.nsfw = nsfw;
+    }
+
+    pub fn is_nsfw(&self) -> bool {
+        self.nsfw
+    }
+
+    pub fn set_comments_enabled(&mut self, enabled: bool) {
+        self.comments_enabled = enabled;
+    }
+
+    pub fn are_comments_enabled(&self) -> bool {
+        self.comments_enabled
+    }
+
+    pub fn pin_comment(&mut self, comment_id: usize) {
+        self.pinned_comment_id = Some(comment_id);
+    }
+
+    pub fn unpin_comment(&mut self) {
+        self.pinned_comment_id = None;
+    }
+
+    pub fn get_pinned_comment_id(&self) -> Option<usize> {
+        self.pinned_comment_id
+    }
+
+    // comment ids with the pinned one moved to the front
+    pub fn get_comments_pinned_first(&self) -> Vec<usize> {
+        let mut comments = self.comments.clone();
+        if let Some(index) = comments.iter().position(|&x| Some(x) == self.pinned_comment_id) {
+            let pinned = comments.remove(index);
+            comments.insert(0, pinned);
+        }
+        comments
+    }
+
     pub fn add_comment(&mut self, comment_id: usize) {
         self.comments.push(comment_id);
     }
@@ -70,8 +246,54 @@ impl Post {
         self.downvotes.remove(&account_id)
     }
 
-    pub fn get_points(&self) -> usize {
-        self.upvotes.len() - self.downvotes.len()
+    // reacting the same way twice removes the reaction, a different one replaces it
+    pub fn toggle_reaction(&mut self, account_id: AccountId, reaction: String) {
+        if self.reactions.get(&account_id) == Some(&reaction) {
+            self.reactions.remove(&account_id);
+        } else {
+            self.reactions.insert(account_id, reaction);
+        }
+    }
+
+    pub fn count_reactions(&self, reaction: &str) -> u64 {
+        self.reactions.values().filter(|x| *x == reaction).count() as u64
+    }
+
+    pub fn get_points(&self) -> i64 {
+        self.upvotes.len() as i64 - self.downvotes.len() as i64
+    }
+
+    pub fn add_view(&mut self) {
+        self.views += 1;
+    }
+
+    pub fn get_views(&self) -> u64 {
+        self.views
+    }
+
+    pub fn set_excerpt(&mut self, excerpt: String) {
+        self.excerpt = excerpt;
+        self.excerpt_explicit = true;
+    }
+
+    pub fn get_excerpt(&self) -> String {
+        self.excerpt.clone()
+    }
+
+    pub fn set_cover_image_url(&mut self, cover_image_url: String) {
+        self.cover_image_url = cover_image_url;
+    }
+
+    pub fn get_cover_image_url(&self) -> String {
+        self.cover_image_url.clone()
+    }
+
+    pub fn set_slug(&mut self, slug: String) {
+        self.slug = slug;
+    }
+
+    pub fn get_slug(&self) -> String {
+        self.slug.clone()
     }
 
     pub fn get_title(&self) -> String {
@@ -82,6 +304,10 @@ impl Post {
         self.post_id
     }
 
+    pub fn get_created_at(&self) -> u64 {
+        self.created_at
+    }
+
     pub fn get_author(&self) -> AccountId {
         self.author.clone()
     }
@@ -90,19 +316,69 @@ impl Post {
         self.comments.clone()
     }
 
+    // leaves the post without comment ids, for responses that page the comments separately
+    pub fn take_comments(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.comments)
+    }
+
     pub fn remove_comment(&mut self, comment_id: usize) -> bool {
         //return false if comment_id is not found
         if let Some(index) = self.comments.iter().position(|&x| x == comment_id) {
             self.comments.remove(index);
+            if self.pinned_comment_id == Some(comment_id) {
+                self.pinned_comment_id = None;
+            }
             return true;
         }
         false
     }
 
+    // total_donated only counts NEAR, token amounts aren't comparable
     pub fn add_donation_logs(&mut self, donation_log: DonationLog) {
+        if donation_log.is_near() {
+            self.total_donated = U128(self.total_donated.0 + donation_log.get_amount());
+        }
         self.donation_logs.push(donation_log);
     }
 
+    pub fn get_donation_logs(&self) -> Vec<DonationLog> {
+        self.donation_logs.clone()
+    }
+
+    pub fn get_donation_log(&self, donation_id: usize) -> Option<DonationLog> {
+        self.donation_logs.iter().find(|x| x.get_donation_id() == donation_id).cloned()
+    }
+
+    pub fn get_donation_count(&self) -> u64 {
+        self.donation_logs.len() as u64
+    }
+
+    pub fn redact_donation_message(&mut self, donation_id: usize) -> bool {
+        match self.donation_logs.iter_mut().find(|x| x.get_donation_id() == donation_id) {
+            Some(donation_log) => {
+                donation_log.redact_message();
+                true
+            },
+            None => false,
+        }
+    }
+
+    // NEAR total per donor, biggest donor first
+    pub fn get_donor_totals(&self) -> Vec<(AccountId, u128)> {
+        let mut totals: Vec<(AccountId, u128)> = Vec::new();
+
+        for donation_log in self.donation_logs.iter().filter(|x| x.is_near()) {
+            let donor = donation_log.get_donor();
+            match totals.iter_mut().find(|(account_id, _)| *account_id == donor) {
+                Some((_, total)) => *total += donation_log.get_amount(),
+                None => totals.push((donor, donation_log.get_amount())),
+            }
+        }
+
+        totals.sort_by_key(|(_, total)| std::cmp::Reverse(*total));
+        totals
+    }
+
     pub fn get_upvotes(&self) -> HashSet<AccountId> {
         self.upvotes.clone()
     }
@@ -112,10 +388,18 @@ impl Post {
     }
 
     pub fn get_total_donation(&self) -> u128 {
-        self.donation_logs.iter().map(|x| x.get_amount()).sum()
+        self.total_donated.0
     }
 
     pub fn get_body(&self) -> String {
         self.body.clone()
     }
+
+    pub fn get_tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+
+    pub fn get_updated_at(&self) -> u64 {
+        self.updated_at
+    }
 }
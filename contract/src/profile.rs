@@ -0,0 +1,38 @@
+use near_sdk::serde::{Serialize, Deserialize};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+
+#[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Profile {
+    display_name: String,
+    bio: String,
+    avatar_url: String,
+    updated_at: u64,
+}
+
+impl Profile {
+    pub fn new(display_name: String, bio: String, avatar_url: String, updated_at: u64) -> Self {
+        Self {
+            display_name,
+            bio,
+            avatar_url,
+            updated_at,
+        }
+    }
+
+    pub fn get_display_name(&self) -> String {
+        self.display_name.clone()
+    }
+
+    pub fn get_bio(&self) -> String {
+        self.bio.clone()
+    }
+
+    pub fn get_avatar_url(&self) -> String {
+        self.avatar_url.clone()
+    }
+
+    pub fn get_updated_at(&self) -> u64 {
+        self.updated_at
+    }
+}
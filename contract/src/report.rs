@@ -0,0 +1,37 @@
+use near_sdk::AccountId;
+use near_sdk::serde::{Serialize, Deserialize};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+
+use crate::PostId;
+
+#[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Report {
+    post_id: PostId,
+    reporter: AccountId,
+    reason: String,
+    created_at: u64,
+}
+
+impl Report {
+    pub fn new(post_id: PostId, reporter: AccountId, reason: String, created_at: u64) -> Self {
+        Self {
+            post_id,
+            reporter,
+            reason,
+            created_at,
+        }
+    }
+
+    pub fn get_post_id(&self) -> PostId {
+        self.post_id
+    }
+
+    pub fn get_reporter(&self) -> AccountId {
+        self.reporter.clone()
+    }
+
+    pub fn get_reason(&self) -> String {
+        self.reason.clone()
+    }
+}
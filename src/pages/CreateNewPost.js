@@ -4,7 +4,7 @@ import { toast } from "react-toastify";
 import { HashLoader } from "react-spinners";
 import { useNavigate } from "react-router-dom";
 import useQuery from "../hooks/useQuery";
-import { getTransactionUrl } from "../utils/near";
+import { getTransactionUrl, STORAGE_DEPOSIT, STORAGE_GAS } from "../utils/near";
 
 export default function CreateNewPost() {
   //get url params
@@ -32,10 +32,14 @@ export default function CreateNewPost() {
     try {
       setCreatingPost(true);
 
-      const result = window.contract.create_post({
-        title,
-        body,
-      });
+      const result = window.contract.create_post(
+        {
+          title,
+          body,
+        },
+        STORAGE_GAS,
+        STORAGE_DEPOSIT
+      );
 
       await toast.promise(result, {
         pending: "Creating post...",
@@ -8,6 +8,7 @@ import { toast } from "react-toastify";
 import { ThumbDownIcon, ThumbUpIcon } from "@heroicons/react/solid";
 import { Helmet } from "react-helmet";
 import moment from "moment";
+import { STORAGE_DEPOSIT, STORAGE_GAS } from "../../utils/near";
 
 export default function PostView() {
   const { id } = useParams();
@@ -241,10 +242,14 @@ function CommentForm({ post_id }) {
     const post_id = e.target.elements.post_id.value;
 
     try {
-      const result = window.contract.create_comment({
-        body,
-        post_id: parseInt(post_id),
-      });
+      const result = window.contract.create_comment(
+        {
+          body,
+          post_id: parseInt(post_id),
+        },
+        STORAGE_GAS,
+        STORAGE_DEPOSIT
+      );
 
       await toast.promise(result, {
         pending: "Creating comment...",
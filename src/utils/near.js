@@ -1,3 +1,4 @@
+import { utils } from "near-api-js";
 import getConfig from "../config";
 const { networkId } = getConfig(process.env.NODE_ENV || "development");
 
@@ -6,3 +7,8 @@ export function getTransactionUrl(hash) {
     ? `https://explorer.${networkId}.near.org/transactions/${hash}`
     : "/404";
 }
+
+// Calls that store data have to pay for their storage, the contract refunds
+// whatever part of the deposit is not used.
+export const STORAGE_GAS = "100000000000000";
+export const STORAGE_DEPOSIT = utils.format.parseNearAmount("0.1");